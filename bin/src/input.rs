@@ -0,0 +1,121 @@
+//! Parsing of address lists, e.g. exported contact lists.
+
+/// Extract the addresses of an address list.
+///
+/// Blank lines and comment lines starting with `#` are skipped.
+/// Each line may be a bare address (`john@example.com`), optionally quoted,
+/// or a mailbox with a display name (`"John" <john@example.com>`).
+///
+/// If a `column` is given, the input is read as CSV whose first line is the header
+/// and only the values of the column with that name (case-insensitive) are extracted.
+/// Returns `None` if there is no such column.
+pub(crate) fn parse(input: &str, column: Option<&str>) -> Option<Vec<String>> {
+    let mut lines = input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+    let Some(column) = column else {
+        return Some(lines.filter_map(addr_spec).collect());
+    };
+
+    let header = fields(lines.next()?);
+    let index = header
+        .iter()
+        .position(|name| name.trim().eq_ignore_ascii_case(column))?;
+
+    Some(
+        lines
+            .filter_map(|line| fields(line).get(index).and_then(|field| addr_spec(field)))
+            .collect(),
+    )
+}
+
+/// The addr-spec of a mailbox, i.e. the address without display name, angle brackets and quotes
+fn addr_spec(mailbox: &str) -> Option<String> {
+    let mailbox = mailbox.trim();
+    let address = match (mailbox.rfind('<'), mailbox.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &mailbox[start + 1..end],
+        // Only quotes around the whole address, keeping quoted local parts like `"john doe"@…`
+        _ => ['"', '\'']
+            .into_iter()
+            .find_map(|quote| mailbox.strip_prefix(quote)?.strip_suffix(quote))
+            .unwrap_or(mailbox),
+    }
+    .trim();
+
+    (!address.is_empty()).then(|| address.to_string())
+}
+
+/// Split a CSV line into its fields.
+/// Fields may be quoted with `"`, in which case `""` is an escaped quote.
+fn fields(line: &str) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+
+    fields.push(field);
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    #[test]
+    fn display_name() {
+        let input = r#"
+            # exported contacts
+            john@example.com
+
+            "John Doe" <john.doe@example.com>
+            Jane <jane@example.com>
+            'quoted@example.com'
+            "double-quoted@example.com"
+            "john doe"@example.com
+        "#;
+
+        assert_eq!(
+            parse(input, None).unwrap(),
+            [
+                "john@example.com",
+                "john.doe@example.com",
+                "jane@example.com",
+                "quoted@example.com",
+                "double-quoted@example.com",
+                "\"john doe\"@example.com",
+            ]
+        );
+    }
+
+    #[test]
+    fn csv() {
+        let input = r#"Name,E-Mail,Phone
+"Doe, John","""John"" <john@example.com>",123
+Jane,jane@example.com,456
+
+# no address
+Max,,789
+"#;
+
+        assert_eq!(
+            parse(input, Some("e-mail")).unwrap(),
+            ["john@example.com", "jane@example.com"]
+        );
+        assert_eq!(parse(input, Some("address")), None);
+        assert_eq!(parse("", Some("address")), None);
+    }
+}
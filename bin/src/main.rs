@@ -3,10 +3,12 @@
 
 #![warn(clippy::all, clippy::pedantic)]
 
-use std::{env::args, process};
+mod input;
 
-use mailify_lib::{CheckResult, Client};
-use tokio::task;
+use std::{env::args, fs, process, str::FromStr, time::Duration};
+
+use mailify_lib::{CheckResult, Client, Config};
+use tokio::sync::mpsc;
 
 #[tokio::main]
 /// Exits with the following code:
@@ -15,40 +17,119 @@ use tokio::task;
 /// - 1: Incorrect usage of the program
 /// - 2: At least one of the provided addresses is erroneous
 async fn main() {
-    let client = Client::default();
     let code = match args().collect::<Vec<_>>().as_slice() {
         [] => {
             eprintln!("You shouldn't be able to call programs without argv0");
             1
         }
         [argv0] => {
-            eprintln!("Usage: {argv0} [email address]...");
+            eprintln!(
+                "Usage: {argv0} [--concurrency <n>] [--timeout <secs>] [--port <n>] \
+                 [--file <path> [--column <name>]] [email address]..."
+            );
             1
         }
-        [_argv0, addresses @ ..] => check_all(addresses.to_vec(), client).await,
+        [_argv0, arguments @ ..] => match parse_arguments(arguments) {
+            Ok(arguments) => match Client::new(arguments.config()) {
+                Ok(client) => check_all(arguments.addresses, client).await,
+                Err(error) => {
+                    eprintln!("Unable to create the client: {error:?}");
+                    1
+                }
+            },
+            Err(message) => {
+                eprintln!("{message}");
+                1
+            }
+        },
     };
 
     process::exit(code)
 }
 
-/// Check all addresses in parallel.
+/// Parsed command line arguments
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Arguments {
+    addresses: Vec<String>,
+    /// Maximum number of checks running at the same time
+    concurrency: Option<usize>,
+    timeout: Option<Duration>,
+    /// Port of the mail servers
+    port: Option<u16>,
+}
+
+impl Arguments {
+    /// Configuration of the client, with defaults for the options which weren't given
+    fn config(&self) -> Config {
+        let config = Config::default();
+        Config {
+            concurrency: self.concurrency.unwrap_or(config.concurrency),
+            timeout: self.timeout.or(config.timeout),
+            port: self.port.unwrap_or(config.port),
+            ..config
+        }
+    }
+}
+
+/// Parse the options and collect the addresses given as arguments and the addresses
+/// in the file given with `--file`.
+/// `--column` selects the CSV column of the file containing the addresses.
+fn parse_arguments(arguments: &[String]) -> Result<Arguments, String> {
+    let mut parsed = Arguments::default();
+    let mut file = None;
+    let mut column = None;
+
+    let mut arguments = arguments.iter();
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "--file" => file = Some(arguments.next().ok_or("--file requires a path")?),
+            "--column" => column = Some(arguments.next().ok_or("--column requires a name")?),
+            "--concurrency" => parsed.concurrency = Some(number(argument, arguments.next())?),
+            "--timeout" => {
+                parsed.timeout = Some(Duration::from_secs(number(argument, arguments.next())?));
+            }
+            "--port" => parsed.port = Some(number(argument, arguments.next())?),
+            _ => parsed.addresses.push(argument.clone()),
+        }
+    }
+
+    match (file, column) {
+        (Some(file), column) => {
+            let content = fs::read_to_string(file)
+                .map_err(|error| format!("Unable to read {file}: {error}"))?;
+            let addresses = input::parse(&content, column.map(String::as_str))
+                .ok_or_else(|| format!("{file} has no column {}", column.unwrap()))?;
+            parsed.addresses.extend(addresses);
+        }
+        (None, Some(_)) => return Err("--column requires --file".into()),
+        (None, None) => {}
+    }
+
+    Ok(parsed)
+}
+
+/// The number given as value of the option
+fn number<T: FromStr>(option: &str, value: Option<&String>) -> Result<T, String> {
+    value
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| format!("{option} requires a number"))
+}
+
+/// Check all addresses in parallel, at most [`Config::concurrency`] at the same time.
+/// Results are printed as they complete.
 /// Return the program exit code.
 async fn check_all(addresses: Vec<String>, client: Client) -> i32 {
-    let tasks: Vec<_> = addresses
-        .into_iter()
-        .map(|address| {
-            let client = client.clone();
-            task::spawn(async move {
-                let result = client.check(&address).await;
-                println!("{address} - {result}");
-                result
-            })
-        })
-        .collect();
+    let (sender, input) = mpsc::channel(addresses.len().max(1));
+    for address in addresses {
+        // The channel has room for all addresses and the receiver is alive
+        let _ = sender.try_send(address);
+    }
+    drop(sender);
 
     let mut success = true;
-    for task in tasks {
-        let result = task.await.unwrap();
+    let mut results = client.check_channel(input);
+    while let Some((address, result)) = results.recv().await {
+        println!("{address} - {result}");
         if matches!(result, CheckResult::Failure(_)) {
             success = false;
         }
@@ -56,3 +137,60 @@ async fn check_all(addresses: Vec<String>, client: Client) -> i32 {
 
     if success { 0 } else { 2 }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Arguments, parse_arguments};
+
+    fn parse(arguments: &[&str]) -> Result<Arguments, String> {
+        parse_arguments(
+            &arguments
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn options() {
+        let arguments = parse(&[
+            "a@example.com",
+            "--concurrency",
+            "50",
+            "--timeout",
+            "30",
+            "b@example.com",
+            "--port",
+            "2525",
+        ])
+        .unwrap();
+        assert_eq!(
+            arguments,
+            Arguments {
+                addresses: vec!["a@example.com".into(), "b@example.com".into()],
+                concurrency: Some(50),
+                timeout: Some(Duration::from_secs(30)),
+                port: Some(2525),
+            }
+        );
+
+        let config = arguments.config();
+        assert_eq!(config.concurrency, 50);
+        assert_eq!(config.port, 2525);
+
+        // Defaults of the library
+        let config = parse(&["a@example.com"]).unwrap().config();
+        assert_eq!(config.concurrency, 10);
+        assert_eq!(config.port, 25);
+    }
+
+    #[test]
+    fn invalid_options() {
+        assert!(parse(&["--concurrency"]).is_err());
+        assert!(parse(&["--timeout", "soon"]).is_err());
+        assert!(parse(&["--port", "65536"]).is_err());
+        assert!(parse(&["--column", "email"]).is_err());
+    }
+}
@@ -0,0 +1,244 @@
+use std::borrow::Cow;
+
+use crate::{Domain, FailureReason};
+
+/// Maximum length of the local part in octets as per
+/// [RFC5321 section 4.5.3.1.1](https://www.rfc-editor.org/rfc/rfc5321#section-4.5.3.1.1)
+const MAX_LOCAL_PART_LENGTH: usize = 64;
+
+/// Maximum length of the domain in octets as per
+/// [RFC5321 section 4.5.3.1.2](https://www.rfc-editor.org/rfc/rfc5321#section-4.5.3.1.2)
+const MAX_DOMAIN_LENGTH: usize = 255;
+
+/// Maximum length of the whole address, as the path including the angle brackets
+/// is limited to 256 octets by [RFC5321 section 4.5.3.1.3](https://www.rfc-editor.org/rfc/rfc5321#section-4.5.3.1.3)
+const MAX_ADDRESS_LENGTH: usize = 254;
+
+/// Mail address split into its parts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedAddress {
+    /// Part before the last `@`, without surrounding whitespace
+    pub local_part: String,
+    /// Part after the last `@`, without surrounding whitespace
+    pub domain: Domain,
+    /// Whether the domain is a domain literal like `[127.0.0.1]`,
+    /// in which case no DNS lookup is made
+    pub is_ip_literal: bool,
+    /// Whether the local part is a quoted string like `"john doe"`
+    pub is_quoted: bool,
+}
+
+/// Validate and split a mail address without any network requests.
+/// The same rules are applied by [`Client::check`](crate::Client::check).
+///
+/// Roughly follows [RFC5322 section 3.4.1](https://www.rfc-editor.org/rfc/rfc5322#section-3.4.1).
+/// The RFC isn't followed strictly because this is not a format validation library.
+/// Encoding the RFC's full complexity would require additional effort, like supporting comments
+/// (CFWS) which isn't really used in the real world today.
+///
+/// `mailto:` URIs as per [RFC6068](https://www.rfc-editor.org/rfc/rfc6068), e.g. from scraped
+/// links, are accepted as well: the scheme and any query (`?subject=...`) are dropped
+/// and percent-encoded characters are decoded.
+///
+/// # Errors
+///
+/// Returns [`FailureReason::InvalidAddressFormat`] if the address is malformed,
+/// or if a `mailto:` URI contains several addresses
+pub fn parse_address(mail: &str) -> Result<ParsedAddress, FailureReason> {
+    let mail = strip_mailto(mail)?;
+    let (local_part, domain) = mail
+        .rsplit_once('@')
+        .ok_or(FailureReason::InvalidAddressFormat)?;
+
+    // Handle surrounding FWS. Note that we don't handle CFWS.
+    let local_part = local_part.trim();
+    let domain = domain.trim();
+
+    if local_part.is_empty() || domain.is_empty() {
+        return Err(FailureReason::InvalidAddressFormat);
+    }
+
+    let domain = Domain::new(domain).ok_or(FailureReason::InvalidAddressFormat)?;
+
+    // Lengths are limited on the network, where the ASCII form of the domain is used
+    let domain_length = domain.ascii().len();
+    if local_part.len() > MAX_LOCAL_PART_LENGTH
+        || domain_length > MAX_DOMAIN_LENGTH
+        || local_part.len() + 1 + domain_length > MAX_ADDRESS_LENGTH
+    {
+        return Err(FailureReason::InvalidAddressFormat);
+    }
+
+    Ok(ParsedAddress {
+        local_part: local_part.to_string(),
+        is_ip_literal: domain.is_literal(),
+        is_quoted: is_quoted(local_part),
+        domain,
+    })
+}
+
+/// Whether the local part is a quoted string like `"john doe"`
+pub(crate) fn is_quoted(local_part: &str) -> bool {
+    local_part.len() >= 2 && local_part.starts_with('"') && local_part.ends_with('"')
+}
+
+/// The address of a `mailto:` URI, otherwise the input unchanged
+fn strip_mailto(mail: &str) -> Result<Cow<'_, str>, FailureReason> {
+    let trimmed = mail.trim();
+    let Some(uri) = trimmed
+        .get(..7)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("mailto:"))
+        .map(|_| &trimmed[7..])
+    else {
+        return Ok(Cow::Borrowed(mail));
+    };
+
+    let to = uri.split_once('?').map_or(uri, |(to, _)| to);
+    // Commas separate addresses, commas within an address are percent-encoded
+    if to.contains(',') {
+        return Err(FailureReason::InvalidAddressFormat);
+    }
+    percent_decode(to)
+        .map(Cow::Owned)
+        .ok_or(FailureReason::InvalidAddressFormat)
+}
+
+/// Decode `%XX` sequences. `None` if a sequence is invalid or the result isn't UTF-8.
+fn percent_decode(text: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Check the format of a mail address without any network requests,
+/// which is useful for instant feedback in forms.
+/// Internationalized domains are accepted if they can be converted to ASCII.
+/// An address passing this check might still not exist, use [`Client::check`](crate::Client::check) for that.
+///
+/// # Errors
+///
+/// Returns [`FailureReason::InvalidAddressFormat`] if the address is malformed or too long
+pub fn validate_syntax(mail: &str) -> Result<(), FailureReason> {
+    parse_address(mail).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_address, validate_syntax};
+    use crate::FailureReason;
+
+    #[test]
+    fn plain() {
+        let address = parse_address(" john.doe@Example.com ").unwrap();
+        assert_eq!(address.local_part, "john.doe");
+        assert_eq!(address.domain.original(), "Example.com");
+        assert!(!address.is_ip_literal);
+        assert!(!address.is_quoted);
+    }
+
+    #[test]
+    fn quoted() {
+        let address = parse_address(r#""john@doe"@example.com"#).unwrap();
+        assert_eq!(address.local_part, r#""john@doe""#);
+        assert_eq!(address.domain.ascii(), "example.com");
+        assert!(address.is_quoted);
+    }
+
+    #[test]
+    fn ip_literal() {
+        let address = parse_address("hello@[127.0.0.1]").unwrap();
+        assert_eq!(address.local_part, "hello");
+        assert_eq!(address.domain.ascii(), "[127.0.0.1]");
+        assert!(address.is_ip_literal);
+        assert!(!address.is_quoted);
+    }
+
+    #[test]
+    fn mailto() {
+        let address = parse_address("mailto:john.doe@example.com").unwrap();
+        assert_eq!(address.local_part, "john.doe");
+        assert_eq!(address.domain.original(), "example.com");
+
+        let address =
+            parse_address(" MAILTO:john%2Bnews@example.com?subject=Hi&body=Hello%20there ")
+                .unwrap();
+        assert_eq!(address.local_part, "john+news");
+        assert_eq!(address.domain.original(), "example.com");
+
+        for mail in [
+            "mailto:",
+            "mailto:?subject=Hi",
+            "mailto:john@example.com,jane@example.com",
+            "mailto:john%2@example.com",
+        ] {
+            assert_eq!(
+                parse_address(mail),
+                Err(FailureReason::InvalidAddressFormat)
+            );
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        for mail in ["some text", "@", "local-part@", "@domain"] {
+            assert_eq!(
+                parse_address(mail),
+                Err(FailureReason::InvalidAddressFormat)
+            );
+        }
+    }
+
+    #[test]
+    fn syntax() {
+        assert_eq!(validate_syntax("hello@example.com"), Ok(()));
+        assert_eq!(validate_syntax("jörg@bücher.example"), Ok(()));
+        assert_eq!(validate_syntax("hello@[127.0.0.1]"), Ok(()));
+
+        for mail in [
+            "some text",
+            "@",
+            "local-part@",
+            "@domain",
+            "hello@[localhost]",
+        ] {
+            assert_eq!(
+                validate_syntax(mail),
+                Err(FailureReason::InvalidAddressFormat)
+            );
+        }
+    }
+
+    #[test]
+    fn lengths() {
+        let local_part = "a".repeat(64);
+        let label = "b".repeat(63);
+
+        assert_eq!(
+            validate_syntax(&format!("{local_part}@example.com")),
+            Ok(())
+        );
+        assert_eq!(
+            validate_syntax(&format!("a{local_part}@example.com")),
+            Err(FailureReason::InvalidAddressFormat)
+        );
+        assert_eq!(
+            validate_syntax(&format!("a@{label}.{label}.{label}.{label}.ch")),
+            Err(FailureReason::InvalidAddressFormat)
+        );
+        // Local part and domain are fine on their own, but not together
+        assert_eq!(
+            validate_syntax(&format!("{local_part}@{label}.{label}.{label}.ch")),
+            Err(FailureReason::InvalidAddressFormat)
+        );
+    }
+}
@@ -0,0 +1,27 @@
+use std::{fmt::Debug, future::Future, pin::Pin};
+
+use crate::{CheckResult, Client};
+
+/// Boxed future returned by [`VerificationBackend::verify`]
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A way of verifying if a mail address exists.
+///
+/// mailify verifies addresses via SMTP, which is implemented by [`Client`].
+/// When SMTP verification is impossible, for example because outgoing port 25 is blocked,
+/// another backend such as a third-party HTTP verification service can be plugged in
+/// with [`Config::fallback`](crate::Config::fallback).
+/// mailify doesn't ship any such backend, it only provides this trait.
+///
+/// The trait is object-safe and implemented by [`Client`], so applications can depend on
+/// `Arc<dyn VerificationBackend>` instead of a [`Client`] and inject a fake in their tests.
+pub trait VerificationBackend: Debug + Send + Sync {
+    /// Verify if the given address exists
+    fn verify<'a>(&'a self, address: &'a str) -> BoxFuture<'a, CheckResult>;
+}
+
+impl VerificationBackend for Client {
+    fn verify<'a>(&'a self, address: &'a str) -> BoxFuture<'a, CheckResult> {
+        Box::pin(self.check(address))
+    }
+}
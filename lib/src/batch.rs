@@ -0,0 +1,749 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use rand::Rng;
+use tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt},
+    sync::{
+        Semaphore,
+        mpsc::{self, Receiver, Sender},
+    },
+    task::{self, JoinSet},
+    time::{self, Instant},
+};
+
+use crate::{CheckResult, Client, Domain, FailureReason, UncertaintyReason, parse_address};
+
+/// Overall status of a domain, see [`DomainReport`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DomainStatus {
+    /// A mail server of the domain answered
+    Reachable,
+    /// No mail server of the domain answered, e.g. because it has no MX records
+    /// or the addresses are invalid
+    Unreachable,
+    /// A mail server answered and accepts any address of the domain, so the accepted
+    /// addresses say little, see [`DetailedResult::catch_all`](crate::DetailedResult::catch_all)
+    CatchAll,
+}
+
+/// Results of the addresses of a single domain, see [`Client::check_batch_grouped`]
+#[derive(Debug, PartialEq)]
+pub struct DomainReport {
+    /// Domain of the addresses. `None` for addresses with an invalid format.
+    pub domain: Option<Domain>,
+    /// Overall status of the domain
+    pub domain_status: DomainStatus,
+    /// Addresses of the domain with their results, in the given order
+    pub addresses: Vec<(String, CheckResult)>,
+}
+
+/// Progress of a batch of checks, e.g. to display "N of M completed".
+/// Clones share the counters, so a clone can be polled from another task
+/// independently of consuming the results.
+/// See [`Client::check_channel_with_progress`] and [`Client::check_many_with_progress`].
+#[derive(Debug, Clone, Default)]
+pub struct Progress {
+    completed: Arc<AtomicUsize>,
+    total: Arc<AtomicUsize>,
+}
+
+impl Progress {
+    /// Number of addresses whose check completed
+    #[must_use]
+    pub fn completed(&self) -> usize {
+        self.completed.load(Ordering::Relaxed)
+    }
+
+    /// Number of addresses to check
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    fn add_completed(&self, count: usize) {
+        self.completed.fetch_add(count, Ordering::Relaxed);
+    }
+
+    fn add_total(&self, count: usize) {
+        self.total.fetch_add(count, Ordering::Relaxed);
+    }
+}
+
+/// Checks of a domain running and addresses waiting for one of them to finish,
+/// see [`Config::per_domain_concurrency`](crate::Config::per_domain_concurrency)
+#[derive(Default)]
+struct DomainQueue {
+    running: usize,
+    parked: VecDeque<String>,
+}
+
+/// State of [`Client::check_channel_with_progress`]
+struct Dispatcher {
+    client: Client,
+    sender: Sender<(String, CheckResult)>,
+    progress: Progress,
+    /// Permits of [`Config::concurrency`](crate::Config::concurrency), held until
+    /// the result was sent, which provides backpressure
+    semaphore: Arc<Semaphore>,
+    /// Domains with running checks. Entries are removed once they have none.
+    domains: HashMap<Domain, DomainQueue>,
+    /// Number of addresses parked in `domains`
+    parked: usize,
+    tasks: JoinSet<Option<Domain>>,
+    first: bool,
+}
+
+impl Dispatcher {
+    /// Start checking the address, unless its domain is saturated.
+    /// Then the address is parked without occupying one of the permits.
+    async fn receive(&mut self, address: String) {
+        self.progress.add_total(1);
+        // Invalid addresses don't reach any mail server, so they aren't limited
+        let domain = self
+            .client
+            .config
+            .per_domain_concurrency
+            .and_then(|limit| Some((limit.max(1), parse_address(&address).ok()?.domain)));
+        let Some((limit, domain)) = domain else {
+            self.start(address, None).await;
+            return;
+        };
+
+        let queue = self.domains.entry(domain.clone()).or_default();
+        if queue.running < limit {
+            queue.running += 1;
+            self.start(address, Some(domain)).await;
+        } else {
+            queue.parked.push_back(address);
+            self.parked += 1;
+        }
+    }
+
+    /// Start the next address parked for the domain of the finished check, if any
+    async fn finish(&mut self, domain: Option<Domain>) {
+        let Some(domain) = domain else {
+            return;
+        };
+        let Some(queue) = self.domains.get_mut(&domain) else {
+            return;
+        };
+        if let Some(address) = queue.parked.pop_front() {
+            self.parked -= 1;
+            self.start(address, Some(domain)).await;
+        } else {
+            queue.running -= 1;
+            if queue.running == 0 {
+                self.domains.remove(&domain);
+            }
+        }
+    }
+
+    async fn start(&mut self, address: String, domain: Option<Domain>) {
+        if let Some(jitter) = &self.client.config.jitter
+            && !std::mem::take(&mut self.first)
+        {
+            let delay = rand::rng().random_range(jitter.clone());
+            time::sleep(delay).await;
+        }
+
+        let Ok(permit) = self.semaphore.clone().acquire_owned().await else {
+            return;
+        };
+        let client = self.client.clone();
+        let sender = self.sender.clone();
+        let progress = self.progress.clone();
+        self.tasks.spawn(async move {
+            let result = client.check(&address).await;
+            progress.add_completed(1);
+            // An error means that the receiver was dropped, so nobody is interested anymore
+            let _ = sender.send((address, result)).await;
+            drop(permit);
+            domain
+        });
+    }
+}
+
+impl Client {
+    /// Check the addresses received from `addresses` and emit the results as they complete.
+    /// At most [`Config::concurrency`](crate::Config::concurrency) checks run at the same time,
+    /// of which at most [`Config::per_domain_concurrency`](crate::Config::per_domain_concurrency)
+    /// check addresses of the same domain.
+    ///
+    /// Backpressure is respected: if the results aren't consumed,
+    /// no further addresses are taken from the input channel.
+    /// Checks are paced according to [`Config::jitter`](crate::Config::jitter).
+    /// Once the input channel is closed and all pending checks completed,
+    /// the output channel is closed. Dropping the output receiver stops processing.
+    ///
+    /// Must be called from within a Tokio runtime.
+    #[must_use]
+    pub fn check_channel(&self, addresses: Receiver<String>) -> Receiver<(String, CheckResult)> {
+        self.check_channel_with_progress(addresses, &Progress::default())
+    }
+
+    /// Check the addresses like [`Client::check_channel`] and count them in the [`Progress`].
+    /// The total isn't known in advance, so it grows as addresses are received.
+    /// A check counts as completed before its result is emitted.
+    ///
+    /// Must be called from within a Tokio runtime.
+    #[must_use]
+    pub fn check_channel_with_progress(
+        &self,
+        mut addresses: Receiver<String>,
+        progress: &Progress,
+    ) -> Receiver<(String, CheckResult)> {
+        let concurrency = self.config.concurrency.max(1);
+        let (sender, results) = mpsc::channel(concurrency);
+        let mut dispatcher = Dispatcher {
+            client: self.clone(),
+            sender,
+            progress: progress.clone(),
+            semaphore: Arc::new(Semaphore::new(concurrency)),
+            domains: HashMap::new(),
+            parked: 0,
+            tasks: JoinSet::new(),
+            first: true,
+        };
+
+        task::spawn(async move {
+            let mut receiving = true;
+            loop {
+                tokio::select! {
+                    address = addresses.recv(), if receiving && dispatcher.parked < concurrency => {
+                        match address {
+                            Some(_) if dispatcher.sender.is_closed() => break,
+                            Some(address) => dispatcher.receive(address).await,
+                            None => receiving = false,
+                        }
+                    }
+                    Some(finished) = dispatcher.tasks.join_next() => {
+                        dispatcher.finish(finished.ok().flatten()).await;
+                    }
+                    else => break,
+                }
+            }
+
+            while dispatcher.tasks.join_next().await.is_some() {}
+        });
+
+        results
+    }
+
+    /// Check the addresses read from `reader`, one per line, like [`Client::check_channel`],
+    /// e.g. from stdin, a socket or a decompressor. Surrounding whitespace is trimmed
+    /// and empty lines are skipped.
+    ///
+    /// Lines are only read as fast as the results are consumed.
+    /// Reading stops at the end of the input or at the first error, e.g. invalid UTF-8.
+    ///
+    /// Must be called from within a Tokio runtime.
+    #[must_use]
+    pub fn check_reader<R>(&self, reader: R) -> Receiver<(String, CheckResult)>
+    where
+        R: AsyncBufRead + Unpin + Send + 'static,
+    {
+        let (sender, addresses) = mpsc::channel(self.config.concurrency.max(1));
+        task::spawn(async move {
+            let mut lines = reader.lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let address = line.trim();
+                if !address.is_empty() && sender.send(address.to_string()).await.is_err() {
+                    break;
+                }
+            }
+        });
+        self.check_channel(addresses)
+    }
+
+    /// Check all addresses and group the results by domain, e.g. to audit the address list
+    /// of a whole company. Reports are in the order in which the domains first occur.
+    ///
+    /// The mail servers of each domain are resolved once with [`Client::warm_up`].
+    /// If that fails, all addresses of the domain get its result without being checked.
+    /// Otherwise the addresses of a domain are checked one after the other,
+    /// with at most [`Config::concurrency`](crate::Config::concurrency) domains at the same time.
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub async fn check_batch_grouped(&self, addresses: Vec<String>) -> Vec<DomainReport> {
+        let mut groups: Vec<(Option<Domain>, Vec<String>)> = vec![];
+        for address in addresses {
+            let domain = parse_address(&address).ok().map(|parsed| parsed.domain);
+            match groups.iter_mut().find(|(group, _)| *group == domain) {
+                Some((_, group)) => group.push(address),
+                None => groups.push((domain, vec![address])),
+            }
+        }
+
+        let semaphore = Arc::new(Semaphore::new(self.config.concurrency.max(1)));
+        let mut tasks = JoinSet::new();
+        for (index, (domain, addresses)) in groups.into_iter().enumerate() {
+            let client = self.clone();
+            let semaphore = semaphore.clone();
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire().await;
+                (index, client.check_domain(domain, addresses).await)
+            });
+        }
+
+        let mut reports = tasks.join_all().await;
+        reports.sort_by_key(|(index, _)| *index);
+        reports.into_iter().map(|(_, report)| report).collect()
+    }
+
+    /// Check the addresses of a single domain, see [`Client::check_batch_grouped`]
+    async fn check_domain(&self, domain: Option<Domain>, addresses: Vec<String>) -> DomainReport {
+        let prepared = match &domain {
+            Some(domain) => self.warm_up(domain.ascii()).await,
+            None => CheckResult::Failure(FailureReason::InvalidAddressFormat),
+        };
+        // All addresses would fail the same way
+        if prepared != CheckResult::Success {
+            return DomainReport {
+                domain,
+                domain_status: DomainStatus::Unreachable,
+                addresses: addresses
+                    .into_iter()
+                    .map(|address| (address, prepared.clone()))
+                    .collect(),
+            };
+        }
+
+        let mut domain_status = DomainStatus::Unreachable;
+        let mut results = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            let detailed = self.check_detailed(&address).await;
+            if detailed.catch_all == Some(true)
+                || detailed.result == CheckResult::Uncertain(UncertaintyReason::CatchAll)
+            {
+                domain_status = DomainStatus::CatchAll;
+            } else if detailed.peer_addr.is_some() && domain_status == DomainStatus::Unreachable {
+                domain_status = DomainStatus::Reachable;
+            }
+            results.push((address, detailed.result));
+        }
+
+        DomainReport {
+            domain,
+            domain_status,
+            addresses: results,
+        }
+    }
+
+    /// Check all addresses like [`Client::check_channel`], but give up after the `deadline`.
+    /// Results completed by then are returned, the remaining addresses resolve to
+    /// [`UncertaintyReason::Timeout`]. Results are in the order of the addresses.
+    ///
+    /// Checks still running at the deadline are abandoned and end in the background
+    /// at the latest after [`Config::timeout`](crate::Config::timeout).
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub async fn check_many(
+        &self,
+        addresses: Vec<String>,
+        deadline: Duration,
+    ) -> Vec<(String, CheckResult)> {
+        self.check_many_with_progress(addresses, deadline, &Progress::default())
+            .await
+    }
+
+    /// Check all addresses like [`Client::check_many`] and count them in the [`Progress`].
+    /// The total is set to the number of addresses right away. Addresses resolving to
+    /// [`UncertaintyReason::Timeout`] at the deadline count as completed as well,
+    /// so the completed checks reach the total once this returns.
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub async fn check_many_with_progress(
+        &self,
+        addresses: Vec<String>,
+        deadline: Duration,
+        progress: &Progress,
+    ) -> Vec<(String, CheckResult)> {
+        self.check_ordered(addresses, Some(Instant::now() + deadline), progress)
+            .await
+    }
+
+    /// Check all addresses with [`Client::check_channel`] and return the results
+    /// in the order of the addresses, giving up at the deadline if there is one
+    pub(crate) async fn check_ordered(
+        &self,
+        addresses: Vec<String>,
+        deadline: Option<Instant>,
+        progress: &Progress,
+    ) -> Vec<(String, CheckResult)> {
+        progress.add_total(addresses.len());
+        let (sender, input) = mpsc::channel(addresses.len().max(1));
+        for address in &addresses {
+            // The channel has room for all addresses and the receiver is alive
+            let _ = sender.try_send(address.clone());
+        }
+        drop(sender);
+
+        let mut results: Vec<_> = addresses.into_iter().map(|a| (a, None)).collect();
+        let mut checked = self.check_channel(input);
+        loop {
+            let received = match deadline {
+                Some(deadline) => time::timeout_at(deadline, checked.recv())
+                    .await
+                    .unwrap_or(None),
+                None => checked.recv().await,
+            };
+            let Some((address, result)) = received else {
+                break;
+            };
+
+            // Addresses might occur more than once
+            if let Some((_, slot)) = results
+                .iter_mut()
+                .find(|(a, slot)| *a == address && slot.is_none())
+            {
+                *slot = Some(result);
+                progress.add_completed(1);
+            }
+        }
+
+        progress.add_completed(results.iter().filter(|(_, slot)| slot.is_none()).count());
+        results
+            .into_iter()
+            .map(|(address, result)| {
+                let result = result.unwrap_or(CheckResult::Uncertain(UncertaintyReason::Timeout));
+                (address, result)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        io::Cursor,
+        net::IpAddr,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+        sync::mpsc,
+        time::{self, Instant},
+    };
+
+    use super::{DomainReport, DomainStatus, Progress};
+    use crate::{CheckResult, Client, Config, Domain, FailureReason};
+
+    /// Current and maximum number of sessions, in total and per local address
+    #[derive(Default)]
+    struct Sessions {
+        current: HashMap<Option<IpAddr>, usize>,
+        max: HashMap<Option<IpAddr>, usize>,
+    }
+
+    impl Sessions {
+        fn change(&mut self, ip: IpAddr, started: bool) {
+            for key in [None, Some(ip)] {
+                let current = self.current.entry(key).or_default();
+                if started {
+                    *current += 1;
+                } else {
+                    *current -= 1;
+                }
+                let current = *current;
+                let max = self.max.entry(key).or_default();
+                *max = (*max).max(current);
+            }
+        }
+    }
+
+    /// Accept SMTP sessions, each taking a while and accepting the address
+    async fn serve_slowly(listener: TcpListener, sessions: Arc<Mutex<Sessions>>) {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let sessions = sessions.clone();
+            tokio::spawn(async move {
+                let ip = stream.local_addr().unwrap().ip();
+                sessions.lock().unwrap().change(ip, true);
+                time::sleep(Duration::from_millis(20)).await;
+
+                let (reader, mut writer) = stream.into_split();
+                writer.write_all(b"220 mock\r\n").await.unwrap();
+                let mut lines = BufReader::new(reader).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    // The session ends with the response to the recipient,
+                    // after which the next check may start right away
+                    if line.starts_with("RCPT") {
+                        sessions.lock().unwrap().change(ip, false);
+                    }
+                    writer.write_all(b"250 OK\r\n").await.unwrap();
+                }
+            });
+        }
+    }
+
+    #[tokio::test]
+    async fn per_domain_concurrency() {
+        let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+        let client = Client::new(Config {
+            port: listener.local_addr().unwrap().port(),
+            concurrency: 5,
+            per_domain_concurrency: Some(2),
+            ..Default::default()
+        })
+        .unwrap();
+        let sessions = Arc::new(Mutex::new(Sessions::default()));
+        let server = tokio::spawn(serve_slowly(listener, sessions.clone()));
+
+        let ips = ["127.0.0.1", "127.0.0.2", "127.0.0.3", "127.0.0.4"];
+        // Grouped by domain, so without a limit per domain all checks would go to the first one
+        let addresses: Vec<_> = ips
+            .iter()
+            .flat_map(|ip| (0..6).map(move |i| format!("user{i}@[{ip}]")))
+            .collect();
+        let results = client
+            .check_ordered(addresses, None, &Progress::default())
+            .await;
+        server.abort();
+
+        assert!(
+            results
+                .iter()
+                .all(|(_, result)| *result == CheckResult::Success)
+        );
+        let sessions = sessions.lock().unwrap();
+        assert!(sessions.max[&None] <= 5);
+        for ip in ips {
+            assert!(sessions.max[&Some(ip.parse().unwrap())] <= 2);
+        }
+    }
+
+    #[tokio::test]
+    async fn saturated_domain_first() {
+        let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+        let client = Client::new(Config {
+            port: listener.local_addr().unwrap().port(),
+            concurrency: 5,
+            per_domain_concurrency: Some(2),
+            ..Default::default()
+        })
+        .unwrap();
+        let sessions = Arc::new(Mutex::new(Sessions::default()));
+        let server = tokio::spawn(serve_slowly(listener, sessions.clone()));
+
+        let saturated = (0..6).map(|i| format!("user{i}@[127.0.0.1]"));
+        let others = ["127.0.0.2", "127.0.0.3", "127.0.0.4"]
+            .iter()
+            .flat_map(|ip| (0..2).map(move |i| format!("user{i}@[{ip}]")));
+        let results = client
+            .check_ordered(
+                saturated.chain(others).collect(),
+                None,
+                &Progress::default(),
+            )
+            .await;
+        server.abort();
+
+        assert!(
+            results
+                .iter()
+                .all(|(_, result)| *result == CheckResult::Success)
+        );
+        let sessions = sessions.lock().unwrap();
+        // The addresses waiting for the first domain don't hold back the others
+        assert_eq!(sessions.max[&None], 5);
+        assert_eq!(sessions.max[&Some("127.0.0.1".parse().unwrap())], 2);
+    }
+
+    #[tokio::test]
+    async fn check_batch_grouped() {
+        let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+        let closed = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let unreachable = closed.local_addr().unwrap().port();
+        drop(closed);
+        let client = Client::new(Config {
+            port: listener.local_addr().unwrap().port(),
+            ports: HashMap::from([("127.0.0.2".into(), unreachable)]),
+            ..Default::default()
+        })
+        .unwrap();
+        let server = tokio::spawn(serve_slowly(listener, Arc::default()));
+
+        let addresses = ["a@[127.0.0.1]", "x@[127.0.0.2]", "invalid", "b@[127.0.0.1]"];
+        let reports = client
+            .check_batch_grouped(addresses.map(String::from).to_vec())
+            .await;
+        server.abort();
+
+        assert_eq!(
+            reports[0],
+            DomainReport {
+                domain: Domain::new("[127.0.0.1]"),
+                domain_status: DomainStatus::Reachable,
+                addresses: vec![
+                    ("a@[127.0.0.1]".into(), CheckResult::Success),
+                    ("b@[127.0.0.1]".into(), CheckResult::Success),
+                ],
+            }
+        );
+        assert_eq!(reports[1].domain, Domain::new("[127.0.0.2]"));
+        assert_eq!(reports[1].domain_status, DomainStatus::Unreachable);
+        assert!(matches!(
+            reports[1].addresses.as_slice(),
+            [(_, CheckResult::Failure(FailureReason::IoError(_)))]
+        ));
+        assert_eq!(
+            reports[2],
+            DomainReport {
+                domain: None,
+                domain_status: DomainStatus::Unreachable,
+                addresses: vec![(
+                    "invalid".into(),
+                    CheckResult::Failure(FailureReason::InvalidAddressFormat)
+                )],
+            }
+        );
+        assert_eq!(reports.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn check_channel() {
+        let client = Client::new(Config {
+            concurrency: 2,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let (sender, input) = mpsc::channel(1);
+        let mut results = client.check_channel(input);
+
+        let addresses = ["@c", "a", "b@", "d"];
+        tokio::spawn(async move {
+            for address in addresses {
+                sender.send(address.to_string()).await.unwrap();
+            }
+        });
+
+        let mut checked = Vec::new();
+        while let Some((address, result)) = results.recv().await {
+            assert_eq!(
+                result,
+                CheckResult::Failure(FailureReason::InvalidAddressFormat)
+            );
+            checked.push(address);
+        }
+
+        checked.sort();
+        assert_eq!(checked, addresses);
+    }
+
+    #[tokio::test]
+    async fn check_reader() {
+        let client = Client::new(Config {
+            concurrency: 2,
+            ..Default::default()
+        })
+        .unwrap();
+        let input = "@c\n  a \n\nb@\r\nd";
+        let mut results = client.check_reader(Cursor::new(input.as_bytes().to_vec()));
+
+        let mut checked = Vec::new();
+        while let Some((address, result)) = results.recv().await {
+            assert_eq!(
+                result,
+                CheckResult::Failure(FailureReason::InvalidAddressFormat)
+            );
+            checked.push(address);
+        }
+
+        checked.sort();
+        assert_eq!(checked, ["@c", "a", "b@", "d"]);
+    }
+
+    #[tokio::test]
+    async fn progress() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = Client::new(Config {
+            port: listener.local_addr().unwrap().port(),
+            concurrency: 2,
+            ..Default::default()
+        })
+        .unwrap();
+        let server = tokio::spawn(serve_slowly(listener, Arc::default()));
+
+        let progress = Progress::default();
+        let shared = progress.clone();
+        let display = tokio::spawn(async move {
+            // Counts only grow and never exceed the total
+            let mut last = 0;
+            while shared.completed() < 5 {
+                let completed = shared.completed();
+                assert!(completed >= last && completed <= shared.total());
+                last = completed;
+                time::sleep(Duration::from_millis(1)).await;
+            }
+        });
+
+        let addresses = [
+            "a@[127.0.0.1]",
+            "invalid",
+            "b@[127.0.0.1]",
+            "c@[127.0.0.1]",
+            "d",
+        ];
+        let results = client
+            .check_many_with_progress(
+                addresses.map(String::from).to_vec(),
+                Duration::from_secs(10),
+                &progress,
+            )
+            .await;
+        server.abort();
+        display.await.unwrap();
+
+        assert_eq!(results.len(), 5);
+        assert_eq!(progress.total(), 5);
+        assert_eq!(progress.completed(), 5);
+
+        // The total of a channel grows as addresses are received
+        let progress = Progress::default();
+        let (sender, input) = mpsc::channel(2);
+        let mut results = client.check_channel_with_progress(input, &progress);
+        for address in ["a", "b"] {
+            sender.send(address.to_string()).await.unwrap();
+        }
+        drop(sender);
+        while results.recv().await.is_some() {}
+        assert_eq!(progress.total(), 2);
+        assert_eq!(progress.completed(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn jitter() {
+        let client = Client::new(Config {
+            jitter: Some(Duration::from_secs(1)..=Duration::from_secs(2)),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let (sender, input) = mpsc::channel(3);
+        for address in ["a", "b", "c"] {
+            sender.send(address.to_string()).await.unwrap();
+        }
+        drop(sender);
+
+        let start = Instant::now();
+        let mut results = client.check_channel(input);
+        while results.recv().await.is_some() {}
+
+        // No delay before the first check
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_secs(2));
+        assert!(elapsed <= Duration::from_secs(4));
+    }
+}
@@ -0,0 +1,144 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+
+use tokio::time::Instant;
+
+use crate::CheckResult;
+
+/// How long results are cached, depending on their kind, see [`Config::result_cache`](crate::Config::result_cache).
+/// A zero duration disables caching of that kind of result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheTtl {
+    /// Time to live of [`CheckResult::Success`]. Defaults to one day.
+    pub success: Duration,
+    /// Time to live of [`CheckResult::Failure`]. Defaults to one day.
+    pub failure: Duration,
+    /// Time to live of [`CheckResult::Uncertain`]. Defaults to five minutes.
+    /// Uncertain results might change soon, e.g. once greylisting passed, but caching them
+    /// briefly avoids hammering mail servers which block us with repeated checks.
+    pub uncertain: Duration,
+}
+
+impl Default for CacheTtl {
+    fn default() -> Self {
+        Self {
+            success: Duration::from_hours(24),
+            failure: Duration::from_hours(24),
+            uncertain: Duration::from_mins(5),
+        }
+    }
+}
+
+impl CacheTtl {
+    const fn of(&self, result: &CheckResult) -> Duration {
+        match result {
+            CheckResult::Success => self.success,
+            CheckResult::Failure(_) => self.failure,
+            CheckResult::Uncertain(_) => self.uncertain,
+        }
+    }
+}
+
+/// Results of previous checks by address, shared between clones of a [`Client`](crate::Client)
+#[derive(Debug, Default)]
+pub(crate) struct ResultCache {
+    entries: Mutex<Entries>,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    results: HashMap<String, (CheckResult, Instant)>,
+    /// Number of entries at which expired entries are removed,
+    /// so the cache doesn't grow with addresses which are never checked again
+    purge_at: usize,
+}
+
+impl ResultCache {
+    /// Cached result of the address, unless it expired
+    pub(crate) fn get(&self, address: &str) -> Option<CheckResult> {
+        let mut entries = self.lock();
+        match entries.results.get(address) {
+            Some((result, expires)) if *expires > Instant::now() => Some(result.clone()),
+            Some(_) => {
+                entries.results.remove(address);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub(crate) fn insert(&self, address: &str, result: CheckResult, ttl: &CacheTtl) {
+        let ttl = ttl.of(&result);
+        if ttl.is_zero() {
+            return;
+        }
+
+        let now = Instant::now();
+        let mut entries = self.lock();
+        if entries.results.len() >= entries.purge_at {
+            entries.results.retain(|_, (_, expires)| *expires > now);
+            entries.purge_at = (entries.results.len() * 2).max(64);
+        }
+        entries
+            .results
+            .insert(address.to_string(), (result, now + ttl));
+    }
+
+    pub(crate) fn invalidate(&self, address: &str) {
+        self.lock().results.remove(address);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::time;
+
+    use super::{CacheTtl, ResultCache};
+    use crate::{CheckResult, FailureReason, UncertaintyReason};
+
+    #[tokio::test(start_paused = true)]
+    async fn expiry() {
+        let cache = ResultCache::default();
+        let ttl = CacheTtl {
+            success: Duration::from_secs(30),
+            failure: Duration::ZERO,
+            uncertain: Duration::from_secs(10),
+        };
+        let greylisted = CheckResult::Uncertain(UncertaintyReason::Greylisted);
+        cache.insert("a@example.com", CheckResult::Success, &ttl);
+        cache.insert("b@example.com", greylisted.clone(), &ttl);
+        cache.insert(
+            "c@example.com",
+            CheckResult::Failure(FailureReason::NoSuchAddress),
+            &ttl,
+        );
+
+        assert_eq!(cache.get("a@example.com"), Some(CheckResult::Success));
+        assert_eq!(cache.get("b@example.com"), Some(greylisted));
+        assert_eq!(cache.get("c@example.com"), None);
+
+        time::advance(Duration::from_secs(11)).await;
+        assert_eq!(cache.get("a@example.com"), Some(CheckResult::Success));
+        assert_eq!(cache.get("b@example.com"), None);
+
+        time::advance(Duration::from_secs(20)).await;
+        assert_eq!(cache.get("a@example.com"), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn invalidate() {
+        let cache = ResultCache::default();
+        cache.insert("a@example.com", CheckResult::Success, &CacheTtl::default());
+        cache.invalidate("a@example.com");
+        assert_eq!(cache.get("a@example.com"), None);
+    }
+}
@@ -0,0 +1,171 @@
+use async_smtp::response::Response;
+
+use crate::CheckResult;
+
+/// Classification of mail server responses defined by the operator,
+/// e.g. to encode knowledge about a specific provider without forking the crate.
+/// See [`Config::classification_rules`](crate::Config::classification_rules).
+///
+/// Patterns are globs: `*` matches any sequence of characters and `?` a single character.
+/// A rule matches a response if both of its patterns match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassificationRule {
+    /// Pattern of the status code, matched against the basic code (e.g. `550`)
+    /// and the enhanced code as per [RFC3463](https://www.rfc-editor.org/rfc/rfc3463)
+    /// if the message starts with one (e.g. `5.1.1`), e.g. `5.7.*` or `45?`.
+    /// `None` matches any code.
+    pub code: Option<String>,
+    /// Pattern of the message, matched ignoring case. Lines of multiline responses
+    /// are joined with a space. `None` matches any message.
+    pub message: Option<String>,
+    /// Whether the message pattern must match the whole message.
+    /// Otherwise it may match anywhere in the message.
+    pub anchored: bool,
+    /// Result of a check ending with a matching response
+    pub result: CheckResult,
+}
+
+impl ClassificationRule {
+    /// Rule classifying responses with a matching status code
+    #[must_use]
+    pub fn new(code: &str, result: CheckResult) -> Self {
+        Self {
+            code: Some(code.into()),
+            message: None,
+            anchored: false,
+            result,
+        }
+    }
+
+    /// Only match responses whose message contains the pattern
+    #[must_use]
+    pub fn with_message(mut self, pattern: &str) -> Self {
+        self.message = Some(pattern.into());
+        self
+    }
+
+    fn matches(&self, response: &Response) -> bool {
+        let code_matches = self.code.as_deref().is_none_or(|pattern| {
+            glob(pattern, &response.code.to_string())
+                || enhanced_code(response).is_some_and(|code| glob(pattern, code))
+        });
+
+        let message_matches = self.message.as_deref().is_none_or(|pattern| {
+            let pattern = pattern.to_lowercase();
+            let message = response.message.join(" ").to_lowercase();
+            if self.anchored {
+                glob(&pattern, &message)
+            } else {
+                glob(&format!("*{pattern}*"), &message)
+            }
+        });
+
+        code_matches && message_matches
+    }
+}
+
+/// Result of the first rule matching the response
+pub(crate) fn classify(rules: &[ClassificationRule], response: &Response) -> Option<CheckResult> {
+    rules
+        .iter()
+        .find(|rule| rule.matches(response))
+        .map(|rule| rule.result.clone())
+}
+
+/// Enhanced status code the message starts with, e.g. `5.1.1`
+fn enhanced_code(response: &Response) -> Option<&str> {
+    let code = response.message.first()?.split_whitespace().next()?;
+    let is_code = code.split('.').count() == 3
+        && code
+            .split('.')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()));
+    is_code.then_some(code)
+}
+
+/// Whether the text matches the glob pattern
+fn glob(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and of the text it was matched against, to backtrack
+    let mut star = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use async_smtp::response::Response;
+
+    use super::{ClassificationRule, classify, glob};
+    use crate::{CheckResult, FailureReason, UncertaintyReason};
+
+    fn response(response: &str) -> Response {
+        response.parse().unwrap()
+    }
+
+    #[test]
+    fn globs() {
+        assert!(glob("5.7.*", "5.7.1"));
+        assert!(glob("45?", "451"));
+        assert!(glob("*", ""));
+        assert!(glob("*quota*", "user is over quota today"));
+        assert!(!glob("45?", "4510"));
+        assert!(!glob("5.7.*", "5.1.1"));
+    }
+
+    #[test]
+    fn rules() {
+        let unknown = CheckResult::Failure(FailureReason::NoSuchAddress);
+        let rules = [
+            ClassificationRule::new("5.7.1", unknown.clone()).with_message("MAILBOX DISABLED"),
+            ClassificationRule {
+                code: None,
+                message: Some("try again*".into()),
+                anchored: true,
+                result: CheckResult::Uncertain(UncertaintyReason::Greylisted),
+            },
+        ];
+
+        assert_eq!(
+            classify(
+                &rules,
+                &response("550 5.7.1 Mailbox disabled for this recipient\r\n")
+            ),
+            Some(unknown)
+        );
+        assert_eq!(
+            classify(&rules, &response("550 5.7.1 Access denied\r\n")),
+            None
+        );
+        assert_eq!(
+            classify(&rules, &response("451 Try again later\r\n")),
+            Some(CheckResult::Uncertain(UncertaintyReason::Greylisted))
+        );
+        assert_eq!(
+            classify(&rules, &response("451 Please try again later\r\n")),
+            None
+        );
+    }
+}
@@ -0,0 +1,63 @@
+use std::{
+    collections::HashSet,
+    sync::{Mutex, PoisonError},
+};
+
+use crate::Domain;
+
+/// Number of different domains whose mail servers must be unreachable
+/// before blocked outbound traffic is assumed
+pub(crate) const BLOCKED_THRESHOLD: usize = 3;
+
+/// Tracks connection failures across checks to detect blocked outbound SMTP traffic.
+///
+/// A single unreachable mail server is most likely the domain's fault,
+/// but if the mail servers of several different domains are unreachable
+/// and none is reachable, it's far more likely that our own network blocks the traffic.
+/// This is an inference and can be wrong, e.g. when checking several domains
+/// which share a broken mail server.
+#[derive(Debug, Default)]
+pub(crate) struct Connectivity {
+    /// Domains whose mail servers couldn't be connected to since the last successful connection
+    unreachable: Mutex<HashSet<Domain>>,
+}
+
+impl Connectivity {
+    /// Record whether connecting to the mail server of the domain succeeded.
+    /// Returns whether outbound traffic seems to be blocked.
+    pub(crate) fn record(&self, domain: &Domain, connected: bool) -> bool {
+        let mut unreachable = self
+            .unreachable
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        if connected {
+            unreachable.clear();
+            return false;
+        }
+
+        unreachable.insert(domain.clone());
+        unreachable.len() >= BLOCKED_THRESHOLD
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Connectivity;
+    use crate::Domain;
+
+    #[test]
+    fn distinct_domains() {
+        let connectivity = Connectivity::default();
+        let domain = |d| Domain::new(d).unwrap();
+
+        assert!(!connectivity.record(&domain("a.example"), false));
+        assert!(!connectivity.record(&domain("a.example"), false));
+        assert!(!connectivity.record(&domain("b.example"), false));
+        assert!(connectivity.record(&domain("c.example"), false));
+
+        // Reachable mail servers prove that the traffic isn't blocked
+        assert!(!connectivity.record(&domain("d.example"), true));
+        assert!(!connectivity.record(&domain("c.example"), false));
+    }
+}
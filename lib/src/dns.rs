@@ -0,0 +1,348 @@
+use std::net::{IpAddr, Ipv4Addr};
+
+use hickory_resolver::{
+    Name, ResolveError, Resolver, TokioResolver,
+    config::{NameServerConfigGroup, ResolverConfig, ResolverOpts},
+    name_server::TokioConnectionProvider,
+    proto::xfer::Protocol,
+    system_conf::read_system_conf,
+};
+
+use crate::Domain;
+
+/// Protocol used to talk to the DNS resolver
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DnsTransport {
+    /// Plain DNS over UDP, falling back on TCP for large responses
+    #[default]
+    Udp,
+    /// Plain DNS over TCP
+    Tcp,
+    /// DNS-over-TLS as per [RFC7858](https://www.rfc-editor.org/rfc/rfc7858),
+    /// which hides the lookups from the local network. Requires a [`DnsUpstream`].
+    Tls,
+    /// DNS-over-HTTPS as per [RFC8484](https://www.rfc-editor.org/rfc/rfc8484),
+    /// which hides the lookups from the local network. Requires a [`DnsUpstream`].
+    Https,
+}
+
+/// Upstream DNS resolver
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DnsUpstream {
+    /// Addresses of the resolver
+    pub addresses: Vec<IpAddr>,
+    /// Port of the resolver. Defaults to the standard port of the [`DnsTransport`].
+    pub port: Option<u16>,
+    /// Name in the certificate of the resolver, required by [`DnsTransport::Tls`] and [`DnsTransport::Https`]
+    pub tls_name: Option<String>,
+}
+
+impl DnsUpstream {
+    /// Cloudflare's public resolver, see <https://www.cloudflare.com/dns/>
+    #[must_use]
+    pub fn cloudflare() -> Self {
+        Self {
+            addresses: vec![
+                IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1)),
+                IpAddr::V4(Ipv4Addr::new(1, 0, 0, 1)),
+            ],
+            port: None,
+            tls_name: Some("cloudflare-dns.com".into()),
+        }
+    }
+}
+
+/// Whether search domains are appended to the domains of addresses, see [`DnsConfig::search`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum SearchDomains {
+    /// Query domains as fully-qualified names, so `example.com` never resolves
+    /// as e.g. `example.com.corp.example`
+    #[default]
+    Disabled,
+    /// Apply the search list of the system's configuration, e.g. `search` in `/etc/resolv.conf`,
+    /// like the system's resolver does. There is none with a [`DnsConfig::upstream`].
+    System,
+    /// Apply the given search list, e.g. `corp.example`, instead of the system's
+    Custom(Vec<String>),
+}
+
+/// How mail servers are looked up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DnsConfig {
+    /// Protocol used to talk to the resolver
+    pub transport: DnsTransport,
+    /// Resolver to use. If `None`, the resolvers configured on the system are used
+    /// (e.g. in `/etc/resolv.conf`), which only supports plain DNS.
+    /// The resolver is built once per [`Client`](crate::Client).
+    pub upstream: Option<DnsUpstream>,
+    /// Validate the MX records with DNSSEC as per [RFC4035](https://www.rfc-editor.org/rfc/rfc4035),
+    /// so spoofed records are detected. Records failing validation result in
+    /// [`UncertaintyReason::DnsResolverError`](crate::UncertaintyReason::DnsResolverError).
+    /// Records of unsigned domains are still accepted. Disabled by default.
+    pub dnssec: bool,
+    /// Resolver to use if there is no `upstream` and the system's configuration can't be read,
+    /// e.g. in minimal container images without `/etc/resolv.conf`.
+    /// Defaults to [`DnsUpstream::cloudflare`]. If `None`, building the [`Client`](crate::Client)
+    /// fails with [`ClientBuildError::ResolverUnavailable`](crate::ClientBuildError::ResolverUnavailable) instead.
+    pub fallback_upstream: Option<DnsUpstream>,
+    /// Whether search domains are appended to the domains of addresses before looking up
+    /// their mail servers, e.g. in corporate networks resolving short names.
+    /// Defaults to [`SearchDomains::Disabled`], which behaves the same in every environment.
+    pub search: SearchDomains,
+    /// Domains with more dots than this are queried as is before appending the search domains,
+    /// others only after. Defaults to the system's configuration, usually 1.
+    /// Only relevant with [`DnsConfig::search`].
+    pub ndots: Option<usize>,
+}
+
+impl Default for DnsConfig {
+    fn default() -> Self {
+        Self {
+            transport: DnsTransport::default(),
+            upstream: None,
+            dnssec: false,
+            fallback_upstream: Some(DnsUpstream::cloudflare()),
+            search: SearchDomains::default(),
+            ndots: None,
+        }
+    }
+}
+
+impl DnsConfig {
+    /// Whether a resolver can be built from this configuration
+    pub(crate) fn is_valid(&self) -> bool {
+        if let SearchDomains::Custom(search) = &self.search
+            && search.iter().any(|domain| Name::from_utf8(domain).is_err())
+        {
+            return false;
+        }
+
+        match (&self.upstream, self.transport) {
+            (None, DnsTransport::Udp | DnsTransport::Tcp) => true,
+            (None, DnsTransport::Tls | DnsTransport::Https) => false,
+            (Some(upstream), DnsTransport::Udp | DnsTransport::Tcp) => {
+                !upstream.addresses.is_empty()
+            }
+            (Some(upstream), DnsTransport::Tls | DnsTransport::Https) => {
+                !upstream.addresses.is_empty() && upstream.tls_name.is_some()
+            }
+        }
+    }
+}
+
+/// Build a resolver according to the configuration.
+/// The configuration must be valid, see [`DnsConfig::is_valid`].
+pub(crate) fn resolver(config: &DnsConfig) -> Result<TokioResolver, ResolveError> {
+    resolver_with(config, read_system_conf)
+}
+
+/// Like [`resolver`], but reads the system's configuration with `system_conf`
+fn resolver_with(
+    config: &DnsConfig,
+    system_conf: impl FnOnce() -> Result<(ResolverConfig, ResolverOpts), ResolveError>,
+) -> Result<TokioResolver, ResolveError> {
+    let (resolver_config, mut options) = if let Some(upstream) = &config.upstream {
+        (
+            upstream_config(config.transport, upstream),
+            ResolverOpts::default(),
+        )
+    } else {
+        match (system_conf(), &config.fallback_upstream) {
+            (Ok((system, options)), _) => (system_config(config.transport, system), options),
+            (Err(_), Some(fallback)) => (
+                upstream_config(config.transport, fallback),
+                ResolverOpts::default(),
+            ),
+            (Err(error), None) => return Err(error),
+        }
+    };
+    options.validate = config.dnssec;
+    if let Some(ndots) = config.ndots {
+        options.ndots = ndots;
+    }
+
+    let resolver_config = match &config.search {
+        SearchDomains::Disabled | SearchDomains::System => resolver_config,
+        SearchDomains::Custom(search) => ResolverConfig::from_parts(
+            resolver_config.domain().cloned(),
+            search
+                .iter()
+                .filter_map(|domain| Name::from_utf8(domain).ok())
+                .collect(),
+            NameServerConfigGroup::from(resolver_config.name_servers().to_vec()),
+        ),
+    };
+    Ok(
+        Resolver::builder_with_config(resolver_config, TokioConnectionProvider::default())
+            .with_options(options)
+            .build(),
+    )
+}
+
+/// Name to look up the mail servers of the domain with, see [`DnsConfig::search`]
+pub(crate) fn query_name(domain: &Domain, config: &DnsConfig) -> String {
+    if config.search == SearchDomains::Disabled {
+        // Fully-qualified, so no search domains are appended
+        format!("{}.", domain.ascii())
+    } else {
+        domain.ascii().to_string()
+    }
+}
+
+/// Reverse DNS name (PTR record) of the address, if it has one
+pub(crate) async fn ptr_name(address: IpAddr, resolver: &TokioResolver) -> Option<String> {
+    let lookup = resolver.reverse_lookup(address).await.ok()?;
+    lookup.iter().next().map(ToString::to_string)
+}
+
+/// Configuration of the resolvers configured on the system
+fn system_config(transport: DnsTransport, system: ResolverConfig) -> ResolverConfig {
+    if transport != DnsTransport::Tcp {
+        return system;
+    }
+
+    let name_servers: Vec<_> = system
+        .name_servers()
+        .iter()
+        .filter(|server| server.protocol == Protocol::Tcp)
+        .cloned()
+        .collect();
+    ResolverConfig::from_parts(
+        system.domain().cloned(),
+        system.search().to_vec(),
+        NameServerConfigGroup::from(name_servers),
+    )
+}
+
+/// Configuration of the given upstream resolver
+fn upstream_config(transport: DnsTransport, upstream: &DnsUpstream) -> ResolverConfig {
+    let addresses = &upstream.addresses;
+    let tls_name = upstream.tls_name.clone().unwrap_or_default();
+    let name_servers = match transport {
+        DnsTransport::Udp => {
+            NameServerConfigGroup::from_ips_clear(addresses, upstream.port.unwrap_or(53), true)
+        }
+        DnsTransport::Tcp => NameServerConfigGroup::from(
+            NameServerConfigGroup::from_ips_clear(addresses, upstream.port.unwrap_or(53), true)
+                .into_inner()
+                .into_iter()
+                .filter(|server| server.protocol == Protocol::Tcp)
+                .collect::<Vec<_>>(),
+        ),
+        DnsTransport::Tls => NameServerConfigGroup::from_ips_tls(
+            addresses,
+            upstream.port.unwrap_or(853),
+            tls_name,
+            true,
+        ),
+        DnsTransport::Https => NameServerConfigGroup::from_ips_https(
+            addresses,
+            upstream.port.unwrap_or(443),
+            tls_name,
+            true,
+        ),
+    };
+
+    ResolverConfig::from_parts(None, vec![], name_servers)
+}
+
+#[cfg(test)]
+mod tests {
+    use hickory_resolver::{ResolveError, proto::xfer::Protocol};
+
+    use super::{DnsConfig, DnsTransport, DnsUpstream, resolver, resolver_with};
+
+    fn protocols(config: &DnsConfig) -> Vec<(Protocol, u16)> {
+        resolver(config)
+            .unwrap()
+            .config()
+            .name_servers()
+            .iter()
+            .map(|server| (server.protocol, server.socket_addr.port()))
+            .collect()
+    }
+
+    #[test]
+    fn upstream_transport() {
+        let config = |transport| DnsConfig {
+            transport,
+            upstream: Some(DnsUpstream::cloudflare()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            protocols(&config(DnsTransport::Tls)),
+            [(Protocol::Tls, 853), (Protocol::Tls, 853)]
+        );
+        assert_eq!(
+            protocols(&config(DnsTransport::Https)),
+            [(Protocol::Https, 443), (Protocol::Https, 443)]
+        );
+        assert_eq!(
+            protocols(&config(DnsTransport::Tcp)),
+            [(Protocol::Tcp, 53), (Protocol::Tcp, 53)]
+        );
+        assert!(protocols(&config(DnsTransport::Udp)).contains(&(Protocol::Udp, 53)));
+    }
+
+    #[test]
+    fn validity() {
+        assert!(DnsConfig::default().is_valid());
+
+        let tls = DnsConfig {
+            transport: DnsTransport::Tls,
+            ..Default::default()
+        };
+        assert!(!tls.is_valid());
+
+        let tls = DnsConfig {
+            upstream: Some(DnsUpstream {
+                tls_name: None,
+                ..DnsUpstream::cloudflare()
+            }),
+            ..tls
+        };
+        assert!(!tls.is_valid());
+    }
+
+    #[test]
+    fn dnssec() {
+        let validates = |config: &DnsConfig| resolver(config).unwrap().options().validate;
+
+        assert!(!validates(&DnsConfig::default()));
+        assert!(validates(&DnsConfig {
+            dnssec: true,
+            ..Default::default()
+        }));
+        assert!(validates(&DnsConfig {
+            transport: DnsTransport::Https,
+            upstream: Some(DnsUpstream::cloudflare()),
+            dnssec: true,
+            ..Default::default()
+        }));
+    }
+
+    #[test]
+    fn missing_system_conf() {
+        let missing = || {
+            Err(ResolveError::from(
+                "No such file or directory: /etc/resolv.conf",
+            ))
+        };
+
+        let resolver = resolver_with(&DnsConfig::default(), missing).unwrap();
+        let addresses: Vec<_> = resolver
+            .config()
+            .name_servers()
+            .iter()
+            .map(|server| server.socket_addr.ip())
+            .collect();
+        assert!(addresses.contains(&DnsUpstream::cloudflare().addresses[0]));
+
+        let config = DnsConfig {
+            fallback_upstream: None,
+            ..Default::default()
+        };
+        assert!(resolver_with(&config, missing).is_err());
+    }
+}
@@ -0,0 +1,216 @@
+use std::{
+    fmt::Display,
+    hash::Hash,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+use hickory_resolver::Name;
+
+/// Domain part of a mail address.
+///
+/// Internationalized domain names (IDN) are kept in two forms:
+/// the original form as provided by the user, which is used when reporting results,
+/// and the ASCII form (A-label, e.g. `xn--bcher-kva.ch`), which is used on the network.
+/// Two domains are equal if their ASCII forms are equal.
+///
+/// A trailing dot marking a fully-qualified domain (e.g. `example.com.`) is kept in the
+/// original form but not in the ASCII form, as mail servers commonly reject it in addresses.
+/// Mail servers are always looked up as fully-qualified domains, so `example.com.`
+/// and `example.com` behave the same.
+///
+/// The ASCII form of a domain literal is normalized to the form of
+/// [RFC5321 section 4.1.3](https://www.rfc-editor.org/rfc/rfc5321#section-4.1.3),
+/// e.g. `[ipv6:2001:DB8:0::1]` becomes `[IPv6:2001:db8::1]`.
+#[derive(Debug, Clone)]
+pub struct Domain {
+    original: String,
+    ascii: String,
+}
+
+impl Domain {
+    /// Parse a domain or a domain literal (e.g. `[127.0.0.1]` or `[IPv6:::1]`).
+    /// Returns `None` if the domain isn't a valid IDN or the literal isn't a valid address.
+    #[must_use]
+    pub fn new(domain: &str) -> Option<Self> {
+        let ascii = if Self::is_literal_form(domain) {
+            match address_literal(&domain[1..domain.len() - 1])? {
+                IpAddr::V4(ip) => format!("[{ip}]"),
+                IpAddr::V6(ip) => format!("[IPv6:{ip}]"),
+            }
+        } else {
+            let ascii = Name::from_utf8(domain).ok()?.to_ascii().to_lowercase();
+            match ascii.strip_suffix('.') {
+                // The root domain isn't a mail domain
+                Some("") => return None,
+                Some(ascii) => ascii.to_string(),
+                None => ascii,
+            }
+        };
+
+        Some(Self {
+            original: domain.to_string(),
+            ascii,
+        })
+    }
+
+    /// The domain exactly as provided by the user
+    #[must_use]
+    pub fn original(&self) -> &str {
+        &self.original
+    }
+
+    /// The ASCII (A-label) form used for DNS and SMTP
+    #[must_use]
+    pub fn ascii(&self) -> &str {
+        &self.ascii
+    }
+
+    /// The normalized Unicode (U-label) form
+    #[must_use]
+    pub fn unicode(&self) -> String {
+        if self.is_literal() {
+            return self.ascii.clone();
+        }
+
+        Name::from_ascii(&self.ascii).map_or_else(|_| self.ascii.clone(), |name| name.to_utf8())
+    }
+
+    /// Whether this is a domain literal like `[127.0.0.1]` rather than a domain name
+    #[must_use]
+    pub fn is_literal(&self) -> bool {
+        Self::is_literal_form(&self.original)
+    }
+
+    /// IP address of a domain literal
+    pub(crate) fn ip(&self) -> Option<IpAddr> {
+        self.is_literal()
+            .then(|| address_literal(&self.ascii[1..self.ascii.len() - 1]))
+            .flatten()
+    }
+
+    /// Whether the given domain in any form, e.g. from the configuration, is this domain.
+    /// All comparisons with domains go through the ASCII form, so they ignore case and IDN forms.
+    pub(crate) fn matches(&self, domain: &str) -> bool {
+        Self::new(domain).is_some_and(|domain| domain == *self)
+    }
+
+    fn is_literal_form(domain: &str) -> bool {
+        domain.len() >= 2 && domain.starts_with('[') && domain.ends_with(']')
+    }
+}
+
+/// Address literal as per [RFC5321 section 4.1.3](https://www.rfc-editor.org/rfc/rfc5321#section-4.1.3),
+/// without the surrounding brackets
+fn address_literal(literal: &str) -> Option<IpAddr> {
+    match literal.get(..5) {
+        Some(tag) if tag.eq_ignore_ascii_case("IPv6:") => {
+            literal[5..].parse::<Ipv6Addr>().ok().map(IpAddr::V6)
+        }
+        _ => literal.parse::<Ipv4Addr>().ok().map(IpAddr::V4),
+    }
+}
+
+impl PartialEq for Domain {
+    fn eq(&self, other: &Self) -> bool {
+        self.ascii == other.ascii
+    }
+}
+
+impl Eq for Domain {}
+
+impl Hash for Domain {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.ascii.hash(state);
+    }
+}
+
+impl Display for Domain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.original)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::Domain;
+
+    #[test]
+    fn keeps_original_form() {
+        let domain = Domain::new("BÜcher.Example").unwrap();
+        assert_eq!(domain.original(), "BÜcher.Example");
+        assert_eq!(domain.ascii(), "xn--bcher-kva.example");
+        assert_eq!(domain.unicode(), "bücher.example");
+        assert_eq!(domain.to_string(), "BÜcher.Example");
+    }
+
+    #[test]
+    fn round_trip() {
+        for input in ["BÜcher.Example", "bücher.example", "xn--bcher-kva.example"] {
+            let domain = Domain::new(input).unwrap();
+            assert_eq!(Domain::new(domain.ascii()).unwrap(), domain);
+            assert_eq!(Domain::new(&domain.unicode()).unwrap(), domain);
+            assert_eq!(domain.original(), input);
+        }
+    }
+
+    #[test]
+    fn mixed_case_equality() {
+        assert_eq!(
+            Domain::new("Münich.DE").unwrap(),
+            Domain::new("münich.de").unwrap()
+        );
+        assert_eq!(
+            Domain::new("Example.COM").unwrap(),
+            Domain::new("example.com").unwrap()
+        );
+
+        let domain = Domain::new("BÜcher.Example").unwrap();
+        assert!(domain.matches("bücher.example"));
+        assert!(domain.matches("XN--BCHER-KVA.example."));
+        assert!(!domain.matches("buecher.example"));
+    }
+
+    #[test]
+    fn trailing_dot() {
+        let domain = Domain::new("Example.com.").unwrap();
+        assert_eq!(domain.original(), "Example.com.");
+        assert_eq!(domain.ascii(), "example.com");
+        assert_eq!(domain, Domain::new("example.com").unwrap());
+
+        assert_eq!(Domain::new("."), None);
+        assert_eq!(Domain::new("example.com.."), None);
+    }
+
+    #[test]
+    fn domain_literal() {
+        let domain = Domain::new("[127.0.0.1]").unwrap();
+        assert!(domain.is_literal());
+        assert_eq!(domain.ascii(), "[127.0.0.1]");
+        assert_eq!(domain.unicode(), "[127.0.0.1]");
+
+        assert_eq!(domain.ip(), Some(Ipv4Addr::LOCALHOST.into()));
+
+        let domain = Domain::new("[ipv6:2001:DB8:0::1]").unwrap();
+        assert!(domain.is_literal());
+        assert_eq!(domain.original(), "[ipv6:2001:DB8:0::1]");
+        assert_eq!(domain.ascii(), "[IPv6:2001:db8::1]");
+        assert_eq!(domain, Domain::new("[IPv6:2001:db8::1]").unwrap());
+        assert_eq!(domain.ip(), "2001:db8::1".parse().ok());
+        assert_eq!(Domain::new("example.com").unwrap().ip(), None);
+
+        for invalid in [
+            "[]",
+            "[",
+            "[example.com]",
+            "[127.0.0.256]",
+            "[::1]",
+            "[IPv6:1.2.3.4]",
+            "[127.0.0.1].",
+            "[IPv6:::1].",
+        ] {
+            assert_eq!(Domain::new(invalid), None);
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use tokio::sync::mpsc::Sender;
+
+use crate::CheckResult;
+
+/// Progress of a check, emitted to [`Config::events`](crate::Config::events)
+/// together with the checked address, e.g. to drive a live display
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckEvent {
+    /// Looking up the mail servers of the domain
+    Resolving,
+    /// Connecting to the mail server. Emitted for every mail server and port tried.
+    Connecting { host: String, port: u16 },
+    /// Introducing ourselves with EHLO or HELO
+    Ehlo,
+    /// Announcing the recipient with RCPT TO
+    RcptSent,
+    /// The check completed with the final result
+    Completed(CheckResult),
+}
+
+/// Emits the events of the check of a single address
+#[derive(Debug, Clone)]
+pub(crate) struct Emitter {
+    sender: Sender<(String, CheckEvent)>,
+    address: String,
+}
+
+impl Emitter {
+    pub(crate) fn new(sender: Sender<(String, CheckEvent)>, address: &str) -> Self {
+        Self {
+            sender,
+            address: address.to_string(),
+        }
+    }
+
+    pub(crate) fn emit(&self, event: CheckEvent) {
+        // Never hold up the check: events are dropped if the receiver doesn't keep up
+        let _ = self.sender.try_send((self.address.clone(), event));
+    }
+}
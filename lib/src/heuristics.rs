@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use async_smtp::response::{Category, Detail, Response, Severity};
 
 use crate::{CheckResult, FailureReason, UncertaintyReason};
@@ -35,6 +37,116 @@ const NO_SUCH_ADDRESS_WORDS: &[&str] = &[
     "double-checking the recipient",
 ];
 
+/// Enhanced status code when encryption is required per
+/// [RFC3207](https://www.rfc-editor.org/rfc/rfc3207#section-4)
+const TLS_REQUIRED_CODES: &[&str] = &["5.7.0"];
+
+/// Textual heuristics for when encryption is required
+const TLS_REQUIRED_WORDS: &[&str] = &["starttls", "tls required", "requires tls"];
+
+/// Textual heuristics to detect greylisting
+const GREYLIST_WORDS: &[&str] = &["greylist", "graylist", "grey-list", "gray-list"];
+
+/// Enhanced status codes commonly used for greylisting
+/// (e.g. "450 4.2.0 Recipient address rejected: Greylisted")
+const GREYLIST_CODES: &[&str] = &["4.2.0", "4.7.1"];
+
+/// Enhanced status codes used when the server rate limits us per
+/// [RFC3463](https://www.rfc-editor.org/rfc/rfc3463#section-3.4)
+const THROTTLING_CODES: &[&str] = &["4.3.2"];
+
+/// Enhanced status codes of temporary problems of the mail server itself per
+/// [RFC3463](https://www.rfc-editor.org/rfc/rfc3463#section-3.4):
+/// other mail system status, mail system full and system incorrectly configured
+const SERVER_ERROR_CODES: &[&str] = &["4.3.0", "4.3.1", "4.3.5"];
+
+/// Textual heuristics to detect rate limiting
+const THROTTLING_WORDS: &[&str] = &[
+    "too many",
+    "rate limit",
+    "ratelimit",
+    "throttl",
+    "slow down",
+];
+
+/// Enhanced status codes rejecting the sender address per
+/// [RFC3463](https://www.rfc-editor.org/rfc/rfc3463#section-3.2),
+/// [RFC7372](https://www.rfc-editor.org/rfc/rfc7372#section-3.2) and
+/// [RFC7505](https://www.rfc-editor.org/rfc/rfc7505#section-4.2)
+const SENDER_REJECTED_CODES: &[&str] = &[
+    "4.1.7", "4.1.8", "5.1.7", "5.1.8", "5.7.23", "5.7.26", "5.7.27",
+];
+
+/// Textual heuristics for when the sender address is rejected
+const SENDER_REJECTED_WORDS: &[&str] = &["sender", "spf", "mail from", "reverse-path"];
+
+/// Class of enhanced status codes for security or policy status per
+/// [RFC3463](https://www.rfc-editor.org/rfc/rfc3463#section-3.8)
+const POLICY_CODE_CLASS: &str = "5.7.";
+
+/// Textual heuristics for rejections based on the policy of the server
+const POLICY_WORDS: &[&str] = &[
+    "policy",
+    "administrative prohibition",
+    "prohibited",
+    "not permitted",
+    "not allowed",
+];
+
+/// Textual heuristics for servers refusing to relay mail to the recipient's domain,
+/// e.g. "554 5.7.1 <john@example.com>: Relay access denied"
+const RELAY_DENIED_WORDS: &[&str] = &[
+    "relay access denied",
+    "relaying denied",
+    "relay denied",
+    "relay not permitted",
+    "relaying not permitted",
+    "relay not allowed",
+    "relaying not allowed",
+    "unable to relay",
+    "we do not relay",
+];
+
+/// Enhanced status codes indicating that our command was malformed or out of sequence per
+/// [RFC3463](https://www.rfc-editor.org/rfc/rfc3463#section-3.6)
+const PROTOCOL_ERROR_CODES: &[&str] = &["5.5.1", "5.5.2", "5.5.4"];
+
+/// Textual heuristics for malformed or out of sequence commands
+const PROTOCOL_ERROR_WORDS: &[&str] = &["syntax error", "command unrecognized", "bad sequence"];
+
+/// Enhanced status codes for messages exceeding a size limit per
+/// [RFC3463](https://www.rfc-editor.org/rfc/rfc3463#section-3.4)
+const SIZE_EXCEEDED_CODES: &[&str] = &["5.2.3", "5.3.4"];
+
+/// Textual heuristics for messages exceeding a size limit
+const SIZE_EXCEEDED_WORDS: &[&str] = &["size exceeds", "message too large", "message too big"];
+
+/// Enhanced status codes of full mailboxes per
+/// [RFC3463](https://www.rfc-editor.org/rfc/rfc3463#section-3.3)
+const MAILBOX_FULL_CODES: &[&str] = &["4.2.2", "5.2.2"];
+
+/// Enhanced status codes of transient failures of an existing mailbox per
+/// [RFC3463](https://www.rfc-editor.org/rfc/rfc3463#section-3.3):
+/// mailbox disabled and mailbox full
+const MAILBOX_EXISTS_TRANSIENT_CODES: &[&str] = &["4.2.1", "4.2.2"];
+
+/// Textual heuristics for full mailboxes
+const MAILBOX_FULL_WORDS: &[&str] = &[
+    "over quota",
+    "quota exceeded",
+    "mailbox full",
+    "mailbox is full",
+];
+
+/// Phrases after which servers state how long to wait before retrying
+const RETRY_AFTER_WORDS: &[&str] = &[
+    "try again in",
+    "try again after",
+    "retry in",
+    "retry after",
+    "wait",
+];
+
 /// Textual heuristics when reverse DNS entries are expected
 const NO_REVERSE_HOSTNAME: &[&str] = &[
     // hi@gmx.de - Uncertain: Unclassified negative SMTP response: gmx.net (mxgmx108) Nemesis ESMTP Service not available No SMTP service Bad DNS PTR resource record. For explanation visit https://postmaster.gmx.net/en/case?c=r0601&i=ip
@@ -43,15 +155,46 @@ const NO_REVERSE_HOSTNAME: &[&str] = &[
     "reverse hostname",
 ];
 
-/// Handle transient and permanent error responses
-pub(crate) fn from_erroneous(response: Response) -> CheckResult {
+/// Which classification wins when a permanent (5xx) response both indicates blocklisting
+/// and an inexistent address, e.g. "550 5.1.1 No such user, sender IP listed at Spamhaus"
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RejectionPrecedence {
+    /// Report [`UncertaintyReason::Blocklisted`], as the server might reject
+    /// every recipient once it blocklisted us
+    #[default]
+    Blocklisted,
+    /// Report [`FailureReason::NoSuchAddress`], trusting that the server
+    /// wouldn't claim an address doesn't exist because of an incidental block
+    NoSuchAddress,
+}
+
+/// Handle permanent (5xx) error responses
+pub(crate) fn handle_permanent(response: Response, precedence: RejectionPrecedence) -> CheckResult {
     use CheckResult::{Failure, Success, Uncertain};
-    if blocklisted(&response) {
+    if tls_required(&response) {
+        Uncertain(UncertaintyReason::TlsRequired)
+    } else if relay_denied(&response) {
+        Uncertain(UncertaintyReason::RelayDenied)
+    } else if precedence == RejectionPrecedence::NoSuchAddress && no_such_address(&response) {
+        Failure(FailureReason::NoSuchAddress)
+    } else if blocklisted(&response).is_some() {
         Uncertain(UncertaintyReason::Blocklisted)
     } else if no_reverse_hostname_found(&response) {
         Uncertain(UncertaintyReason::NoReverseHostname)
+    } else if policy_rejection(&response) {
+        // Policy codes aren't about the existence of the mailbox,
+        // e.g. "550 5.7.1 Recipient address rejected: Access denied by policy"
+        if sender_rejected(&response) {
+            Uncertain(UncertaintyReason::SenderRejected)
+        } else {
+            Uncertain(UncertaintyReason::PolicyRejection)
+        }
     } else if no_such_address(&response) {
         Failure(FailureReason::NoSuchAddress)
+    } else if protocol_error(&response) {
+        Uncertain(UncertaintyReason::ProtocolError)
+    } else if size_exceeded(&response) {
+        Uncertain(UncertaintyReason::MessageSizeExceeded)
     } else if exists(&response) {
         Success
     } else {
@@ -59,6 +202,32 @@ pub(crate) fn from_erroneous(response: Response) -> CheckResult {
     }
 }
 
+/// Handle transient (4xx) error responses
+pub(crate) fn handle_transient(response: Response) -> CheckResult {
+    use CheckResult::{Success, Uncertain};
+    if greylisted_explicitly(&response) {
+        Uncertain(UncertaintyReason::Greylisted)
+    } else if blocklisted(&response).is_some() {
+        Uncertain(UncertaintyReason::Blocklisted)
+    } else if no_reverse_hostname_found(&response) {
+        Uncertain(UncertaintyReason::NoReverseHostname)
+    } else if throttled(&response) {
+        Uncertain(UncertaintyReason::Throttled)
+    } else if message_contains_word(&response.message, SERVER_ERROR_CODES) {
+        Uncertain(UncertaintyReason::ServerError)
+    } else if message_contains_word(&response.message, GREYLIST_CODES) {
+        Uncertain(UncertaintyReason::Greylisted)
+    } else if message_contains_word(&response.message, MAILBOX_EXISTS_TRANSIENT_CODES) {
+        // Unlike for permanent responses, the basic code alone isn't conclusive,
+        // e.g. "421 Service not available" shares the category of "X.2.1"
+        Success
+    } else if local_error(&response) {
+        Uncertain(UncertaintyReason::ServerError)
+    } else {
+        Uncertain(UncertaintyReason::NegativeSmtpResponse(response))
+    }
+}
+
 /// Whether a failure response indicates that the targeted mailbox exists
 fn exists(response: &Response) -> bool {
     // Transient or permanent failure indicating that the mailbox exists
@@ -94,34 +263,381 @@ fn no_reverse_hostname_found_outlook(message: &[String]) -> bool {
         && message_contains_word(message, ["invalid domain name"].as_slice())
 }
 
-fn blocklisted(response: &Response) -> bool {
-    message_contains_word(&response.message, BLOCKLIST_WORDS)
+/// Greylisting mentioned in the message. This takes precedence over
+/// [`BLOCKLIST_WORDS`] for transient responses, as greylisting resolves itself.
+fn greylisted_explicitly(response: &Response) -> bool {
+    message_contains_word(&response.message, GREYLIST_WORDS)
+}
+
+fn throttled(response: &Response) -> bool {
+    message_contains_word(&response.message, THROTTLING_CODES)
+        || message_contains_word(&response.message, THROTTLING_WORDS)
+}
+
+/// Delay the server asks us to wait before retrying, e.g. "try again in 5 minutes".
+/// A number without unit is taken as seconds.
+pub(crate) fn retry_after(response: &Response) -> Option<Duration> {
+    if response.code.severity != Severity::TransientNegativeCompletion {
+        return None;
+    }
+
+    response.message.iter().find_map(|line| {
+        let line = line.to_lowercase();
+        RETRY_AFTER_WORDS.iter().find_map(|word| {
+            let (_, rest) = line.split_once(word)?;
+            let rest = rest.trim_start();
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let amount: u64 = rest[..digits].parse().ok()?;
+
+            let unit = rest[digits..].trim_start();
+            let seconds = if unit.starts_with('h') {
+                amount.saturating_mul(60 * 60)
+            } else if unit.starts_with("min") || unit.starts_with("m ") || unit == "m" {
+                amount.saturating_mul(60)
+            } else {
+                amount
+            };
+            Some(Duration::from_secs(seconds))
+        })
+    })
+}
+
+/// [RFC5321](https://www.rfc-editor.org/rfc/rfc5321.html#section-4.2.3):
+/// 451  Requested action aborted: local error in processing
+fn local_error(response: &Response) -> bool {
+    response.code.severity == Severity::TransientNegativeCompletion
+        && response.code.category == Category::MailSystem
+        && response.code.detail == Detail::One
+}
+
+/// Whether a negative response to `MAIL FROM` rejects the sender address itself,
+/// as opposed to rejecting us (e.g. blocklisting) regardless of the sender.
+pub(crate) fn sender_rejected(response: &Response) -> bool {
+    blocklisted(response).is_none()
+        && !no_reverse_hostname_found(response)
+        && (message_contains_word(&response.message, SENDER_REJECTED_CODES)
+            || message_contains_word(&response.message, SENDER_REJECTED_WORDS))
+}
+
+fn policy_rejection(response: &Response) -> bool {
+    message_contains_word(&response.message, &[POLICY_CODE_CLASS])
+        && message_contains_word(&response.message, POLICY_WORDS)
+}
+
+/// [RFC3207](https://www.rfc-editor.org/rfc/rfc3207#section-4):
+/// 530 Must issue a STARTTLS command first
+fn tls_required(response: &Response) -> bool {
+    (response.has_code(530) || message_contains_word(&response.message, TLS_REQUIRED_CODES))
+        && message_contains_word(&response.message, TLS_REQUIRED_WORDS)
+}
+
+/// Whether the server refused to relay mail to the recipient's domain.
+/// Such a server isn't responsible for the domain, so the response says nothing about the
+/// recipient, e.g. "554 5.7.1 Relay access denied".
+pub(crate) fn relay_denied(response: &Response) -> bool {
+    message_contains_word(&response.message, RELAY_DENIED_WORDS)
+}
+
+/// [RFC5321](https://www.rfc-editor.org/rfc/rfc5321.html#section-4.2.3):
+/// 500  Syntax error, command unrecognized
+/// 501  Syntax error in parameters or arguments
+/// 503  Bad sequence of commands
+///
+/// These indicate a problem with our request rather than with the recipient,
+/// e.g. a server which wants us to authenticate before announcing the recipient.
+fn protocol_error(response: &Response) -> bool {
+    let syntax_code = response.code.severity == Severity::PermanentNegativeCompletion
+        && response.code.category == Category::Syntax
+        && matches!(
+            response.code.detail,
+            Detail::Zero | Detail::One | Detail::Three
+        );
+
+    syntax_code
+        || message_contains_word(&response.message, PROTOCOL_ERROR_CODES)
+        || message_contains_word(&response.message, PROTOCOL_ERROR_WORDS)
+}
+
+fn size_exceeded(response: &Response) -> bool {
+    message_contains_word(&response.message, SIZE_EXCEEDED_CODES)
+        || message_contains_word(&response.message, SIZE_EXCEEDED_WORDS)
+}
+
+/// Whether the response says that the mailbox of the recipient is full
+pub(crate) fn mailbox_full(response: &Response) -> bool {
+    message_contains_word(&response.message, MAILBOX_FULL_CODES)
+        || message_contains_word(&response.message, MAILBOX_FULL_WORDS)
+}
+
+/// Which of the [`BLOCKLIST_WORDS`] the response contains, if any
+fn blocklisted(response: &Response) -> Option<&'static str> {
+    matching_word(&response.message, BLOCKLIST_WORDS)
+}
+
+/// Evidence of why a response was classified as [`UncertaintyReason::Blocklisted`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlocklistEvidence {
+    /// Word of the heuristic found in the response, e.g. `spam`
+    pub word: &'static str,
+    /// Full response of the mail server
+    pub response: Response,
+}
+
+pub(crate) fn blocklist_evidence(response: &Response) -> Option<BlocklistEvidence> {
+    blocklisted(response).map(|word| BlocklistEvidence {
+        word,
+        response: response.clone(),
+    })
 }
 
 fn no_such_address(response: &Response) -> bool {
-    mailbox_unavailable(response) &&
-    // rule out "no access, or command rejected for policy reasons"
-    (
-        // if the service follows RFC3463 and the code clearly indicates
-        // the absence of the recipient address
-        message_contains_word(&response.message, MAILBOX_INEXISTENT_CODES) ||
-        // otherwise fall back on textual heuristics
-        message_contains_word(&response.message, NO_SUCH_ADDRESS_WORDS)
-    )
+    if !mailbox_unavailable(response) {
+        return false;
+    }
+
+    // rule out "no access, or command rejected for policy reasons":
+    // if the service follows RFC3463 and the code clearly indicates
+    // the absence of the recipient address
+    if message_contains_word(&response.message, MAILBOX_INEXISTENT_CODES) {
+        return true;
+    }
+
+    // otherwise fall back on textual heuristics, except for 551 and 553
+    // which are just as well used to deny relaying
+    response.code.detail == Detail::Zero
+        && message_contains_word(&response.message, NO_SUCH_ADDRESS_WORDS)
 }
 
 /// [RFC5321](https://www.rfc-editor.org/rfc/rfc5321.html#section-4.2.3):
 /// 550  Requested action not taken: mailbox unavailable (e.g., mailbox
 /// not found, no access, or command rejected for policy reasons)
+/// 551  User not local; please try <forward-path>
+/// 553  Requested action not taken: mailbox name not allowed (e.g.,
+/// mailbox syntax incorrect)
 fn mailbox_unavailable(response: &Response) -> bool {
     response.code.severity == Severity::PermanentNegativeCompletion
         && response.code.category == Category::MailSystem
-        && response.code.detail == Detail::Zero
+        && matches!(
+            response.code.detail,
+            Detail::Zero | Detail::One | Detail::Three
+        )
 }
 
 fn message_contains_word(message: &[String], words: &[&str]) -> bool {
+    matching_word(message, words).is_some()
+}
+
+/// First of the words contained in the message, ignoring case
+fn matching_word<'a>(message: &[String], words: &[&'a str]) -> Option<&'a str> {
     message
         .iter()
         .map(|line| line.to_lowercase())
-        .any(|line| words.iter().any(|word| line.contains(word)))
+        .find_map(|line| words.iter().find(|word| line.contains(*word)).copied())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use async_smtp::response::Response;
+
+    use super::{RejectionPrecedence, handle_permanent, handle_transient, retry_after};
+    use crate::{CheckResult, FailureReason, UncertaintyReason};
+
+    fn parse(response: &str) -> Option<Duration> {
+        retry_after(&response.parse::<Response>().unwrap())
+    }
+
+    #[test]
+    fn retry_after_phrasings() {
+        assert_eq!(
+            parse("421 4.7.0 Too many connections, try again in 60 seconds\r\n"),
+            Some(Duration::from_mins(1))
+        );
+        assert_eq!(
+            parse("450 4.2.0 Greylisted, please retry in 5 minutes\r\n"),
+            Some(Duration::from_mins(5))
+        );
+        assert_eq!(
+            parse("451 4.7.1 Please try again after 2 min\r\n"),
+            Some(Duration::from_mins(2))
+        );
+        assert_eq!(
+            parse("421-mx.example.com busy\r\n421 Retry after 30\r\n"),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse("450 Rate limited, wait 1 hour\r\n"),
+            Some(Duration::from_hours(1))
+        );
+    }
+
+    #[test]
+    fn no_retry_after() {
+        assert_eq!(parse("450 4.2.0 Please try again later\r\n"), None);
+        assert_eq!(parse("421 Service not available\r\n"), None);
+        // Permanent failures aren't retried
+        assert_eq!(parse("550 Try again in 60 seconds\r\n"), None);
+    }
+
+    #[test]
+    fn server_errors() {
+        let classify = |response: &str| handle_transient(response.parse::<Response>().unwrap());
+        let server_error = CheckResult::Uncertain(UncertaintyReason::ServerError);
+
+        assert_eq!(
+            classify("451 4.3.0 Temporary system problem, try again later\r\n"),
+            server_error
+        );
+        assert_eq!(
+            classify("452 4.3.1 Insufficient system storage\r\n"),
+            server_error
+        );
+        // The basic code alone would indicate an existing mailbox
+        assert_eq!(
+            classify("421 4.3.0 Mail server temporarily rejected message\r\n"),
+            server_error
+        );
+        assert_eq!(
+            classify("451 4.3.5 Server configuration problem\r\n"),
+            server_error
+        );
+        // Closing the connection says nothing about the mailbox
+        for response in [
+            "421 Service not available, closing transmission channel\r\n",
+            "421 4.4.2 Connection timed out\r\n",
+            "422 Try again later\r\n",
+        ] {
+            assert_ne!(classify(response), CheckResult::Success);
+        }
+        assert_eq!(classify("452 4.2.2 Mailbox full\r\n"), CheckResult::Success);
+        // Rate limiting stays distinct
+        assert_eq!(
+            classify("451 4.3.2 Please slow down\r\n"),
+            CheckResult::Uncertain(UncertaintyReason::Throttled)
+        );
+    }
+
+    #[test]
+    fn mailbox_unavailable_details() {
+        let classify = |response: &str| {
+            handle_permanent(
+                response.parse::<Response>().unwrap(),
+                RejectionPrecedence::default(),
+            )
+        };
+        let no_such_address = CheckResult::Failure(FailureReason::NoSuchAddress);
+
+        assert_eq!(
+            classify("551 5.1.6 Recipient has moved, no forwarding address\r\n"),
+            no_such_address
+        );
+        assert_eq!(
+            classify("553 5.1.3 Bad recipient address syntax\r\n"),
+            no_such_address
+        );
+        assert_eq!(
+            classify("553 5.1.1 <john.doe@example.com>: Recipient address rejected\r\n"),
+            no_such_address
+        );
+
+        // Relaying denied, the recipient might well exist
+        assert_ne!(
+            classify("551 User not local; please try <john.doe@example.org>\r\n"),
+            no_such_address
+        );
+        assert_ne!(
+            classify("553 sorry, that domain isn't in my list of allowed rcpthosts\r\n"),
+            no_such_address
+        );
+        assert_ne!(
+            classify("553 5.7.1 Relaying denied, recipient address rejected\r\n"),
+            no_such_address
+        );
+        // Mailbox full
+        assert_ne!(
+            classify("552 5.2.2 Mailbox full, user unknown quota\r\n"),
+            no_such_address
+        );
+    }
+
+    #[test]
+    fn relay_denied() {
+        let classify = |response: &str| {
+            handle_permanent(
+                response.parse::<Response>().unwrap(),
+                RejectionPrecedence::default(),
+            )
+        };
+        let relay_denied = CheckResult::Uncertain(UncertaintyReason::RelayDenied);
+
+        assert_eq!(
+            classify("554 5.7.1 <john@example.com>: Relay access denied\r\n"),
+            relay_denied
+        );
+        assert_eq!(classify("550 5.7.1 Relaying denied\r\n"), relay_denied);
+        assert_eq!(
+            classify("550 Unable to relay for john@example.com\r\n"),
+            relay_denied
+        );
+        assert_eq!(
+            classify("550 5.7.1 Message rejected due to policy\r\n"),
+            CheckResult::Uncertain(UncertaintyReason::PolicyRejection)
+        );
+    }
+
+    #[test]
+    fn policy_rejection() {
+        let classify = |response: &str| {
+            handle_permanent(
+                response.parse::<Response>().unwrap(),
+                RejectionPrecedence::default(),
+            )
+        };
+
+        assert_eq!(
+            classify("550 5.7.1 Message rejected due to policy\r\n"),
+            CheckResult::Uncertain(UncertaintyReason::PolicyRejection)
+        );
+        assert_eq!(
+            classify("550 5.7.1 Recipient address rejected: Access denied by policy\r\n"),
+            CheckResult::Uncertain(UncertaintyReason::PolicyRejection)
+        );
+        assert_eq!(
+            classify("550 5.7.1 Sender address rejected: Not permitted by policy\r\n"),
+            CheckResult::Uncertain(UncertaintyReason::SenderRejected)
+        );
+        // Policy wording alone isn't enough
+        assert!(matches!(
+            classify("550 Rejected due to policy\r\n"),
+            CheckResult::Uncertain(UncertaintyReason::NegativeSmtpResponse(_))
+        ));
+    }
+
+    #[test]
+    fn rejection_precedence() {
+        let response = "550 5.1.1 No such user, sender IP listed at Spamhaus\r\n"
+            .parse::<Response>()
+            .unwrap();
+
+        assert_eq!(
+            handle_permanent(response.clone(), RejectionPrecedence::Blocklisted),
+            CheckResult::Uncertain(UncertaintyReason::Blocklisted)
+        );
+        assert_eq!(
+            handle_permanent(response, RejectionPrecedence::NoSuchAddress),
+            CheckResult::Failure(FailureReason::NoSuchAddress)
+        );
+
+        // Blocklisting alone is unaffected
+        let response = "550 5.7.1 Client host blocked using Spamhaus\r\n"
+            .parse::<Response>()
+            .unwrap();
+        assert_eq!(
+            handle_permanent(response, RejectionPrecedence::NoSuchAddress),
+            CheckResult::Uncertain(UncertaintyReason::Blocklisted)
+        );
+    }
 }
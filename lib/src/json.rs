@@ -0,0 +1,98 @@
+use serde_json::{Value, json};
+
+use crate::{CheckResult, Client, Progress, output};
+
+impl Client {
+    /// Check all addresses like [`Client::check_channel`] and return the results
+    /// as a JSON array in the order of the addresses. Each result is an object of the form
+    ///
+    /// ```json
+    /// {
+    ///   "address": "john.doe@example.com",
+    ///   "result": "uncertain",
+    ///   "reason": "negative_smtp_response",
+    ///   "smtp_code": 554
+    /// }
+    /// ```
+    ///
+    /// - `address`: the address as given
+    /// - `result`: `"success"`, `"uncertain"` or `"failure"`
+    /// - `reason`: `null` for `"success"`, otherwise the [`UncertaintyReason::code`]
+    ///   or [`FailureReason::code`](crate::FailureReason::code), e.g. `"blocklisted"` or `"no_such_address"`
+    /// - `smtp_code`: code of the response for `"negative_smtp_response"`, otherwise `null`
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub async fn check_json(&self, addresses: Vec<String>) -> Value {
+        let results = self
+            .check_ordered(addresses, None, &Progress::default())
+            .await;
+        results
+            .into_iter()
+            .map(|(address, result)| to_json(&address, &result))
+            .collect()
+    }
+}
+
+pub(crate) fn to_json(address: &str, result: &CheckResult) -> Value {
+    let (result, reason, smtp_code) = output::fields(result);
+
+    json!({
+        "address": address,
+        "result": result,
+        "reason": reason,
+        "smtp_code": smtp_code,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use async_smtp::response::Response;
+    use serde_json::json;
+
+    use super::to_json;
+    use crate::{CheckResult, Client, Config, UncertaintyReason};
+
+    #[tokio::test]
+    async fn check_json() {
+        let client = Client::new(Config::default()).unwrap();
+        let results = client.check_json(vec!["a".into(), "@b".into()]).await;
+
+        assert_eq!(
+            results,
+            json!([
+                {
+                    "address": "a",
+                    "result": "failure",
+                    "reason": "invalid_address_format",
+                    "smtp_code": null,
+                },
+                {
+                    "address": "@b",
+                    "result": "failure",
+                    "reason": "invalid_address_format",
+                    "smtp_code": null,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn smtp_code() {
+        let response = "554 Go away\r\n".parse::<Response>().unwrap();
+        let result = CheckResult::Uncertain(UncertaintyReason::NegativeSmtpResponse(response));
+
+        assert_eq!(
+            to_json("john.doe@example.com", &result),
+            json!({
+                "address": "john.doe@example.com",
+                "result": "uncertain",
+                "reason": "negative_smtp_response",
+                "smtp_code": 554,
+            })
+        );
+        assert_eq!(
+            to_json("john.doe@example.com", &CheckResult::Success)["reason"],
+            json!(null)
+        );
+    }
+}
@@ -3,21 +3,95 @@
 
 #![warn(clippy::all, clippy::pedantic)]
 
-use std::{fmt::Display, str::FromStr, time::Duration};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    hash::Hash,
+    net::{IpAddr, Ipv6Addr, SocketAddr},
+    ops::RangeInclusive,
+    str::FromStr,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
 
+pub(crate) mod address;
+pub(crate) mod backend;
+pub(crate) mod batch;
+pub(crate) mod cache;
+pub(crate) mod classification;
+pub(crate) mod connectivity;
+pub(crate) mod dns;
+pub(crate) mod domain;
+pub(crate) mod events;
 pub(crate) mod heuristics;
+#[cfg(feature = "serde")]
+pub(crate) mod json;
+#[cfg(test)]
+pub(crate) mod mock;
+pub(crate) mod output;
+pub(crate) mod overrides;
+pub(crate) mod parent_domain;
+pub(crate) mod pool;
+pub(crate) mod postmaster;
+pub(crate) mod resolution;
+pub(crate) mod reverse_dns;
+pub(crate) mod sender_domain;
+pub(crate) mod smtp;
+pub(crate) mod strategy;
+pub(crate) mod subaddress;
+pub(crate) mod timings;
+pub(crate) mod transcript;
+
+pub use address::{ParsedAddress, parse_address, validate_syntax};
+pub use backend::{BoxFuture, VerificationBackend};
+pub use batch::{DomainReport, DomainStatus, Progress};
+pub use cache::CacheTtl;
+pub use classification::ClassificationRule;
+pub use dns::{DnsConfig, DnsTransport, DnsUpstream, SearchDomains};
+pub use domain::Domain;
+pub use events::CheckEvent;
+pub use heuristics::{BlocklistEvidence, RejectionPrecedence};
+pub use output::{OutputFormat, write_results};
+pub use overrides::ConfigOverrides;
+pub use postmaster::PostmasterStatus;
+pub use resolution::ResolutionProfile;
+pub use reverse_dns::ReverseDns;
+pub use sender_domain::SenderDomainHealth;
+pub use smtp::ResponseLimits;
+pub use strategy::{ChainMode, Strategy, StrategyChain};
+pub use timings::Timings;
+pub use transcript::{Direction, TranscriptLine};
 
 use async_smtp::{
-    EmailAddress, SmtpClient, SmtpTransport,
-    commands::{MailCommand, RcptCommand},
-    extension::ClientId,
-    response::Response,
+    EmailAddress,
+    commands::{EhloCommand, MailCommand, NoopCommand, RsetCommand, VrfyCommand},
+    extension::{ClientId, MailBodyParameter, MailParameter},
+    response::{Response, Severity},
 };
-use hickory_resolver::{Name, ResolveError, proto::rr::rdata::MX};
-use tokio::{io::BufStream, net::TcpStream, time};
-
-/// Email check result
-#[derive(Debug, PartialEq)]
+use cache::ResultCache;
+use connectivity::Connectivity;
+use events::Emitter;
+use hickory_resolver::{Name, ResolveError, TokioResolver, proto::rr::rdata::MX};
+use pool::{Greeted, Pool, Reused};
+use rand::{Rng, seq::SliceRandom};
+use smtp::{Connection, HeloCommand, RecipientCommand};
+use tokio::{
+    io::BufStream,
+    net::{TcpSocket, TcpStream, lookup_host},
+    sync::{OnceCell, mpsc::Sender},
+    time::{self, Instant},
+};
+use transcript::{Recorder, Transcript};
+
+/// Email check result.
+///
+/// Two results are equal if they have the same variant and equal reasons,
+/// e.g. [`UncertaintyReason::NegativeSmtpResponse`]s with the same code and message.
+/// Equal results have equal hashes, so results can be tallied in a `HashMap`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum CheckResult {
     /// Email address exists
     Success,
@@ -27,6 +101,163 @@ pub enum CheckResult {
     Failure(FailureReason),
 }
 
+impl CheckResult {
+    /// Whether mail can be delivered to the address.
+    /// True only for [`CheckResult::Success`].
+    #[must_use]
+    pub const fn is_deliverable(&self) -> bool {
+        matches!(self, CheckResult::Success)
+    }
+
+    /// Whether the address certainly can't receive mail.
+    /// True only for [`CheckResult::Failure`].
+    #[must_use]
+    pub const fn is_undeliverable(&self) -> bool {
+        matches!(self, CheckResult::Failure(_))
+    }
+
+    /// Whether it couldn't be determined if the address exists.
+    /// True only for [`CheckResult::Uncertain`].
+    #[must_use]
+    pub const fn is_indeterminate(&self) -> bool {
+        matches!(self, CheckResult::Uncertain(_))
+    }
+
+    /// Whether the address exists: `Some(true)` for [`CheckResult::Success`],
+    /// `Some(false)` for [`CheckResult::Failure`] and `None` for [`CheckResult::Uncertain`],
+    /// i.e. if it couldn't be determined.
+    #[must_use]
+    pub const fn exists(&self) -> Option<bool> {
+        match self {
+            CheckResult::Success => Some(true),
+            CheckResult::Failure(_) => Some(false),
+            CheckResult::Uncertain(_) => None,
+        }
+    }
+
+    /// Whether checking the address again later might give a definitive result,
+    /// because the mail server or the network reported a temporary problem:
+    /// [`UncertaintyReason::Timeout`], [`UncertaintyReason::AllMxUnreachable`],
+    /// [`UncertaintyReason::Greylisted`], [`UncertaintyReason::Throttled`],
+    /// [`UncertaintyReason::ServerError`], [`UncertaintyReason::DnsResolverError`]
+    /// and transient (4xx)
+    /// [`UncertaintyReason::NegativeSmtpResponse`]s.
+    /// See [`DetailedResult::retry_after`] for how long to wait.
+    #[must_use]
+    pub const fn is_retryable(&self) -> bool {
+        match self {
+            CheckResult::Uncertain(UncertaintyReason::NegativeSmtpResponse(response)) => {
+                matches!(
+                    response.code.severity,
+                    Severity::TransientNegativeCompletion
+                )
+            }
+            CheckResult::Uncertain(reason) => matches!(
+                reason,
+                UncertaintyReason::Timeout
+                    | UncertaintyReason::AllMxUnreachable
+                    | UncertaintyReason::Greylisted
+                    | UncertaintyReason::Throttled
+                    | UncertaintyReason::ServerError
+                    | UncertaintyReason::DnsResolverError(_)
+            ),
+            CheckResult::Success | CheckResult::Failure(_) => false,
+        }
+    }
+
+    /// Stable identifier of the outcome for logs and dashboards: `"success"`,
+    /// otherwise the [`UncertaintyReason::code`] or [`FailureReason::code`],
+    /// e.g. `"blocklisted"` or `"no_such_address"`.
+    /// Unlike the `Debug` and `Display` output, codes never change.
+    #[must_use]
+    pub const fn reason_code(&self) -> &'static str {
+        match self {
+            CheckResult::Success => "success",
+            CheckResult::Uncertain(reason) => reason.code(),
+            CheckResult::Failure(reason) => reason.code(),
+        }
+    }
+
+    /// Combine the results of independent checks of the same address,
+    /// e.g. from different networks or at different times, into a single judgement.
+    ///
+    /// 1. [`CheckResult::Success`] and [`CheckResult::Failure`] take precedence over
+    ///    [`CheckResult::Uncertain`], as an uncertain check says nothing about the address.
+    /// 2. Two successes result in [`CheckResult::Success`], two failures in the failure of `self`.
+    /// 3. A success and a [`FailureReason::InvalidAddressFormat`] result in the failure,
+    ///    as the format doesn't depend on the mail server.
+    ///    Any other success and failure contradict each other and result in
+    ///    [`UncertaintyReason::Conflicting`].
+    /// 4. Of two uncertain results the one with the more informative reason is kept:
+    ///    [`UncertaintyReason::Conflicting`] over [`UncertaintyReason::CatchAll`], which says the
+    ///    address would be accepted, over reasons which likely persist (e.g. [`UncertaintyReason::Blocklisted`]),
+    ///    over reasons which may disappear on retry (e.g. [`UncertaintyReason::Timeout`]
+    ///    or [`UncertaintyReason::Greylisted`]). Equally informative reasons keep the one of `self`.
+    #[must_use]
+    pub fn merge(self, other: CheckResult) -> CheckResult {
+        use CheckResult::{Failure, Success, Uncertain};
+
+        match (self, other) {
+            (Success, Success) => Success,
+            (Failure(reason), Failure(_)) => Failure(reason),
+            (result @ (Success | Failure(_)), Uncertain(_))
+            | (Uncertain(_), result @ (Success | Failure(_))) => result,
+            (Success, Failure(FailureReason::InvalidAddressFormat))
+            | (Failure(FailureReason::InvalidAddressFormat), Success) => {
+                Failure(FailureReason::InvalidAddressFormat)
+            }
+            (Success, Failure(_)) | (Failure(_), Success) => {
+                Uncertain(UncertaintyReason::Conflicting)
+            }
+            (Uncertain(reason), Uncertain(other)) => {
+                if other.informativeness() > reason.informativeness() {
+                    Uncertain(other)
+                } else {
+                    Uncertain(reason)
+                }
+            }
+        }
+    }
+
+    /// The most severe of the results of different addresses, e.g. to reject a signup
+    /// if any of the addresses given fails. Unlike [`CheckResult::merge`] this doesn't
+    /// combine results of the same address but picks the worst of unrelated ones.
+    ///
+    /// [`CheckResult::Failure`] is more severe than [`CheckResult::Uncertain`], which is
+    /// more severe than [`CheckResult::Success`]. Of equally severe results the first is kept.
+    /// No results at all result in [`CheckResult::Success`], as no address failed.
+    #[must_use]
+    pub fn worst(results: impl IntoIterator<Item = CheckResult>) -> CheckResult {
+        results
+            .into_iter()
+            .fold(CheckResult::Success, |worst, result| {
+                if result.severity() > worst.severity() {
+                    result
+                } else {
+                    worst
+                }
+            })
+    }
+
+    /// Rank of the result for [`CheckResult::worst`]
+    const fn severity(&self) -> u8 {
+        match self {
+            CheckResult::Success => 0,
+            CheckResult::Uncertain(_) => 1,
+            CheckResult::Failure(_) => 2,
+        }
+    }
+}
+
+/// Check if the address exists with the default [`Config`], see [`CheckResult::exists`].
+///
+/// `None` means that it couldn't be determined whether the address exists, e.g. because
+/// the mail server blocks verification, not that mailify failed. Use [`Client::check`]
+/// to learn why, and reuse a [`Client`] to check several addresses.
+pub async fn exists(mail: &str) -> Option<bool> {
+    Client::default().check(mail).await.exists()
+}
+
 impl Display for CheckResult {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let message = match self {
@@ -41,48 +272,230 @@ impl Display for CheckResult {
 
 impl From<Result> for CheckResult {
     fn from(result: Result) -> Self {
-        use CheckResult::{Failure, Success, Uncertain};
-        use async_smtp::error::Error::{Permanent, Timeout, Transient};
-        match result {
-            Ok(()) => Success,
-            Err(error) => match error {
-                Error::InvalidAddressFormat => Failure(FailureReason::InvalidAddressFormat),
-                Error::DnsResolution(e) => {
-                    if e.is_no_records_found() {
-                        Failure(FailureReason::NoMxRecords)
-                    } else {
-                        Uncertain(UncertaintyReason::DnsResolverError(e.to_string()))
-                    }
+        check_result(result, RejectionPrecedence::default(), &[])
+    }
+}
+
+/// Classify the outcome of a check, see [`Config::classification_rules`]
+/// and [`Config::rejection_precedence`]
+fn check_result(
+    result: Result,
+    precedence: RejectionPrecedence,
+    rules: &[ClassificationRule],
+) -> CheckResult {
+    use CheckResult::{Failure, Success, Uncertain};
+    use async_smtp::error::Error::{Permanent, Timeout, Transient};
+    match result {
+        Ok(()) => Success,
+        Err(error) => match error {
+            Error::InvalidAddressFormat => Failure(FailureReason::InvalidAddressFormat),
+            Error::DnsResolution(e) => {
+                if e.is_no_records_found() {
+                    Failure(FailureReason::NoMxRecords)
+                } else {
+                    Uncertain(UncertaintyReason::DnsResolverError(e.to_string()))
                 }
-                Error::Smtp(e) => match e {
-                    Transient(r) | Permanent(r) => heuristics::from_erroneous(r),
-                    Timeout(_) => Uncertain(UncertaintyReason::Timeout),
-                    e => Uncertain(UncertaintyReason::SmtpError(e.to_string())),
-                },
-                Error::Io(e) => Failure(FailureReason::IoError(e.to_string())),
-                Error::NoMxRecords => Failure(FailureReason::NoMxRecords),
-                Error::Timeout => Uncertain(UncertaintyReason::Timeout),
+            }
+            Error::Smtp(async_smtp::error::Error::Io(e)) if smtp::protocol_violation(&e) => {
+                Uncertain(UncertaintyReason::ProtocolError)
+            }
+            Error::Smtp(Transient(r) | Permanent(r))
+                if let Some(result) = classification::classify(rules, &r) =>
+            {
+                result
+            }
+            Error::Smtp(e) => match e {
+                Transient(r) => heuristics::handle_transient(r),
+                Permanent(r) => heuristics::handle_permanent(r, precedence),
+                Timeout(_) => Uncertain(UncertaintyReason::Timeout),
+                e => Uncertain(UncertaintyReason::SmtpError(e.to_string())),
             },
-        }
+            Error::Io(e) => Failure(FailureReason::IoError(e.to_string())),
+            Error::NoMxRecords => Failure(FailureReason::NoMxRecords),
+            Error::InvalidMxRecords => Failure(FailureReason::InvalidMxRecords),
+            Error::ParkedDomain => Failure(FailureReason::ParkedDomain),
+            Error::DnssecValidation => Uncertain(UncertaintyReason::DnsResolverError(
+                "DNSSEC validation of the MX records failed".into(),
+            )),
+            Error::Timeout => Uncertain(UncertaintyReason::Timeout),
+            Error::RecipientTimeout => Uncertain(UncertaintyReason::RecipientTimeout),
+            Error::SenderRejected => Uncertain(UncertaintyReason::SenderRejected),
+            Error::EaiUnsupported => Uncertain(UncertaintyReason::EaiUnsupported),
+            // Accepting the connection just to close it, or delaying the greeting
+            // beyond any patience, are common ways of blocking
+            Error::ClosedBeforeGreeting | Error::GreetingTimeout => {
+                Uncertain(UncertaintyReason::Blocklisted)
+            }
+            Error::AllMxUnreachable => Uncertain(UncertaintyReason::AllMxUnreachable),
+            Error::Inconclusive => Uncertain(UncertaintyReason::Inconclusive),
+            Error::VerificationUnsupported => Uncertain(UncertaintyReason::VerificationUnsupported),
+        },
     }
 }
 
+/// Email check result with additional diagnostic information
+#[derive(Debug, PartialEq)]
+pub struct DetailedResult {
+    /// Email check result
+    pub result: CheckResult,
+    /// Domain of the checked address, as typed by the user.
+    /// `None` if the address format is invalid.
+    pub domain: Option<Domain>,
+    /// Last phase the check reached.
+    /// For timeouts this is the phase in which the server stopped responding.
+    /// `None` if the check didn't get past the address validation.
+    pub phase: Option<Phase>,
+    /// Whether the domain accepts mail for any address (catch-all).
+    /// `None` if not probed, see [`Strategy::CatchAllProbe`],
+    /// unless the domain is one of the [`Config::catch_all_providers`].
+    pub catch_all: Option<bool>,
+    /// Response and matched word which caused [`UncertaintyReason::Blocklisted`],
+    /// to audit false positives of the heuristic.
+    /// Kept even if the [`Config::fallback`] determined the final result.
+    pub blocklist_evidence: Option<BlocklistEvidence>,
+    /// Whether the result is a [`CheckResult::Success`] of one of the
+    /// [`Config::trusted_providers`], which can be relied upon.
+    pub authoritative: bool,
+    /// Delay the server asked to wait before retrying, if it stated one
+    /// in a transient rejection (e.g. "try again in 5 minutes").
+    pub retry_after: Option<Duration>,
+    /// Port of the mail server the check connected to, see [`Config::fallback_ports`].
+    /// `None` if no connection was established.
+    pub port: Option<u16>,
+    /// Address of the mail server the check connected to, e.g. for audit logs or abuse reports.
+    /// `None` if no connection was established.
+    pub peer_addr: Option<SocketAddr>,
+    /// Number of usable MX records of the domain. Domains with a single one are more fragile.
+    /// Zero if no MX records were looked up, i.e. for domain literals, [`Config::mx_overrides`]
+    /// and [`Client::check_via`], or if the domain has none.
+    pub mx_count: usize,
+    /// Preference of the MX record of the mail server which answered.
+    /// `None` if no mail server answered, for domain literals, [`Config::mx_overrides`]
+    /// and [`Client::check_via`].
+    pub mx_preference: Option<u16>,
+    /// Whether the more preferred mail servers of the domain were unreachable
+    /// and a less preferred one answered, e.g. only the backup mail server
+    pub used_fallback: bool,
+    /// Whether the greeting of the mail server took longer than the
+    /// [`Config::banner_delay_threshold`], see [`Timings::greeting`]
+    pub banner_delayed: bool,
+    /// Number of connections opened, see [`Config::max_connection_attempts`]
+    pub connection_attempts: usize,
+    /// Result of each strategy checking the address, in the order they ran.
+    /// Empty unless [`ChainMode::Exhaustive`].
+    pub strategy_results: Vec<(Strategy, CheckResult)>,
+    /// Time spent in each phase of the check
+    pub timings: Timings,
+    /// SMTP conversation with the mail server, line by line.
+    /// Useful when debugging or reporting issues with specific providers.
+    pub transcript: Vec<TranscriptLine>,
+}
+
+/// Phases of a check, in chronological order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Phase {
+    /// Looking up the mail server of the domain
+    Dns,
+    /// Establishing the TCP connection
+    Connect,
+    /// Waiting for the greeting of the server
+    Greeting,
+    /// Introducing ourselves with EHLO or HELO
+    Hello,
+    /// Announcing the sender with MAIL FROM
+    MailFrom,
+    /// Asking for the address with VRFY
+    Vrfy,
+    /// Announcing the recipient with RCPT TO
+    Rcpt,
+    /// Announcing a random recipient to detect catch-all domains.
+    /// The result of the check is already determined at this point.
+    CatchAllProbe,
+}
+
 /// There are situations where we cannot determine with
 /// certainty if an address exists. This is mostly due
 /// to blocklists and restrictive measures by email servers.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum UncertaintyReason {
     /// Request timed out.
     /// Unfortunately, ISPs commonly block outgoing port 25 traffic from their customers.
     /// If you see timeouts for different domains this is the most probable issue.
     /// You could try a different ISP, e.g. by using a VPN or switching the network.
     Timeout,
+    /// Mail servers of several different domains were unreachable in a row,
+    /// which most likely means that our network blocks outgoing SMTP traffic (port 25),
+    /// as many ISPs and cloud providers do. This is an inference, not a certainty.
+    /// You could try a different network, e.g. by using a VPN.
+    OutboundPort25Blocked,
+    /// The domain has several MX records, but none of their mail servers accepted
+    /// a connection, e.g. because they are down or firewalled.
+    /// Unlike [`FailureReason::NoMxRecords`], the domain is set up to receive mail.
+    AllMxUnreachable,
+    /// Server answered all our commands until we announced the recipient and then stopped responding.
+    /// This is a common measure to specifically prevent verification of addresses.
+    RecipientTimeout,
     /// Server blocklisted our request.
     /// This normally happens because the server doesn't trust our IP address.
     Blocklisted,
     /// Server expected to find a reverse domain name for our origin IP address.
     NoReverseHostname,
-    /// Got a negative SMTP response
+    /// Server temporarily deferred our request because it doesn't know us yet.
+    /// Retrying after a few minutes usually succeeds.
+    Greylisted,
+    /// Server temporarily refused our request because we sent too many.
+    Throttled,
+    /// Server reported a temporary problem on its side. Retrying later might succeed.
+    ServerError,
+    /// Server couldn't process our request, for example due to a syntax error.
+    /// This indicates a problem with our request rather than with the recipient
+    /// and might be a bug in mailify. Please consider reporting it together with
+    /// the transcript from [`Client::check_detailed`].
+    /// Also reported if a response exceeds the [`Config::response_limits`]
+    /// or doesn't belong to the command we issued, e.g. an unsolicited line.
+    ProtocolError,
+    /// Server refused the size of the message we announced,
+    /// even though no message is ever sent.
+    MessageSizeExceeded,
+    /// Server accepted the address, but it accepts any address of the domain (catch-all).
+    /// See [`Config::demote_catch_all`] and [`Config::catch_all_providers`].
+    CatchAll,
+    /// Server accepted the address, but the provider is known to accept addresses whose
+    /// mailboxes turn out to be undeliverable and to bounce only after delivery.
+    /// See [`Config::deferring_providers`].
+    DeferredVerification,
+    /// None of the [`Config::strategies`] could determine whether the address exists,
+    /// for example because the server doesn't support `VRFY`.
+    Inconclusive,
+    /// Server rejected every configured sender address (`MAIL FROM`).
+    /// See [`Config::sender_addresses`] for common reasons.
+    SenderRejected,
+    /// The local part of the address contains non-ASCII characters, but the server
+    /// doesn't support internationalized addresses (SMTPUTF8) as per
+    /// [RFC6531](https://www.rfc-editor.org/rfc/rfc6531). Unlike the domain,
+    /// the local part can't be converted to ASCII, so the address can't be verified.
+    EaiUnsupported,
+    /// Server only accepts our commands over an encrypted connection (STARTTLS),
+    /// e.g. "530 5.7.0 Must issue a STARTTLS command first".
+    /// mailify doesn't support STARTTLS yet, so the address can't be verified.
+    TlsRequired,
+    /// Server refused to let us verify the address for policy reasons,
+    /// e.g. "550 5.7.1 Message rejected due to policy".
+    /// This says nothing about whether the address exists.
+    PolicyRejection,
+    /// Server refused to relay mail to the domain of the address,
+    /// e.g. "554 5.7.1 Relay access denied". We likely reached a mail server which isn't
+    /// responsible for the domain, so the next mail server of the domain is tried.
+    /// This says nothing about whether the address exists.
+    RelayDenied,
+    /// The domain is one of the [`Config::unverifiable_providers`], whose mail servers
+    /// are known to block verification, so no connection was attempted.
+    VerificationUnsupported,
+    /// Independent checks of the address disagreed on whether it exists,
+    /// see [`CheckResult::merge`]
+    Conflicting,
+    /// Got a negative SMTP response which none of the heuristics recognized.
+    /// Transient (4xx) responses may not persist, see [`CheckResult::is_retryable`].
     NegativeSmtpResponse(Response),
     /// Unexpected SMTP error
     SmtpError(String),
@@ -90,16 +503,94 @@ pub enum UncertaintyReason {
     DnsResolverError(String),
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum FailureReason {
     /// The mail address format is invalid
     InvalidAddressFormat,
     /// The domain has no MX records
     NoMxRecords,
+    /// The domain has MX records, but none of them names a valid host,
+    /// e.g. `mail_server.example.` or an empty name other than the null MX (`.`)
+    InvalidMxRecords,
+    /// The domain has no MX records and its address doesn't run a mail server,
+    /// as is common for parked domains, see [`ImplicitMx`]
+    ParkedDomain,
     /// The mail server does not accept the address
     NoSuchAddress,
     /// Generic IO error
     IoError(String),
+    /// Delivery is unlikely or unverifiable for the given reason,
+    /// only reported with [`ResolutionProfile::Aggressive`]
+    Risky(UncertaintyReason),
+}
+
+impl Hash for UncertaintyReason {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            // `Response` doesn't implement `Hash`, so its parts are hashed instead
+            UncertaintyReason::NegativeSmtpResponse(response) => {
+                response.code.to_string().hash(state);
+                response.message.hash(state);
+            }
+            UncertaintyReason::SmtpError(error) | UncertaintyReason::DnsResolverError(error) => {
+                error.hash(state);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl UncertaintyReason {
+    /// Stable identifier of the reason, the variant name in snake case
+    /// (e.g. `"negative_smtp_response"`), see [`CheckResult::reason_code`]
+    #[must_use]
+    pub const fn code(&self) -> &'static str {
+        match self {
+            UncertaintyReason::Timeout => "timeout",
+            UncertaintyReason::OutboundPort25Blocked => "outbound_port25_blocked",
+            UncertaintyReason::AllMxUnreachable => "all_mx_unreachable",
+            UncertaintyReason::RecipientTimeout => "recipient_timeout",
+            UncertaintyReason::Blocklisted => "blocklisted",
+            UncertaintyReason::NoReverseHostname => "no_reverse_hostname",
+            UncertaintyReason::Greylisted => "greylisted",
+            UncertaintyReason::Throttled => "throttled",
+            UncertaintyReason::ServerError => "server_error",
+            UncertaintyReason::ProtocolError => "protocol_error",
+            UncertaintyReason::MessageSizeExceeded => "message_size_exceeded",
+            UncertaintyReason::CatchAll => "catch_all",
+            UncertaintyReason::DeferredVerification => "deferred_verification",
+            UncertaintyReason::Inconclusive => "inconclusive",
+            UncertaintyReason::SenderRejected => "sender_rejected",
+            UncertaintyReason::EaiUnsupported => "eai_unsupported",
+            UncertaintyReason::TlsRequired => "tls_required",
+            UncertaintyReason::PolicyRejection => "policy_rejection",
+            UncertaintyReason::RelayDenied => "relay_denied",
+            UncertaintyReason::VerificationUnsupported => "verification_unsupported",
+            UncertaintyReason::Conflicting => "conflicting",
+            UncertaintyReason::NegativeSmtpResponse(_) => "negative_smtp_response",
+            UncertaintyReason::SmtpError(_) => "smtp_error",
+            UncertaintyReason::DnsResolverError(_) => "dns_resolver_error",
+        }
+    }
+
+    /// How much the reason tells about the address, see [`CheckResult::merge`]
+    const fn informativeness(&self) -> u8 {
+        match self {
+            UncertaintyReason::Conflicting => 3,
+            UncertaintyReason::CatchAll | UncertaintyReason::DeferredVerification => 2,
+            UncertaintyReason::Timeout
+            | UncertaintyReason::OutboundPort25Blocked
+            | UncertaintyReason::AllMxUnreachable
+            | UncertaintyReason::RecipientTimeout
+            | UncertaintyReason::Greylisted
+            | UncertaintyReason::Throttled
+            | UncertaintyReason::ServerError
+            | UncertaintyReason::SmtpError(_)
+            | UncertaintyReason::DnsResolverError(_) => 0,
+            _ => 1,
+        }
+    }
 }
 
 impl Display for UncertaintyReason {
@@ -108,6 +599,9 @@ impl Display for UncertaintyReason {
             UncertaintyReason::Timeout => {
                 "Connection timed out. This commonly happens if your ISP blocks outgoing SMTP traffic on port 25.".into()
             }
+            UncertaintyReason::OutboundPort25Blocked => "Mail servers of several domains were unreachable. Your network most likely blocks outgoing SMTP traffic on port 25.".into(),
+            UncertaintyReason::AllMxUnreachable => "None of the mail servers of the domain accepted a connection".into(),
+            UncertaintyReason::RecipientTimeout => "Mail server stopped responding when we announced the recipient. It is likely preventing address verification.".into(),
             UncertaintyReason::Blocklisted => "Mail server has blocklisted our requests.".into(),
             UncertaintyReason::NegativeSmtpResponse(response) => {
                 let message  = response.message.join(" ");
@@ -121,12 +615,44 @@ impl Display for UncertaintyReason {
             UncertaintyReason::SmtpError(e) => format!("Unexpected SMPT error: {e}"),
             UncertaintyReason::DnsResolverError(e) => format!("Unexpected DNS resolution error: {e}"),
             UncertaintyReason::NoReverseHostname => "Mail server expected our origin IP to have a DNS PTR for reverse lookup".into(),
+            UncertaintyReason::Greylisted => "Mail server has greylisted our request. Try again in a few minutes.".into(),
+            UncertaintyReason::Throttled => "Mail server is rate limiting our requests. Try again later.".into(),
+            UncertaintyReason::ServerError => "Mail server reported a temporary local error. Try again later.".into(),
+            UncertaintyReason::ProtocolError => "Mail server could not process our request. This might be a bug in mailify.".into(),
+            UncertaintyReason::MessageSizeExceeded => "Mail server refused the announced message size".into(),
+            UncertaintyReason::CatchAll => "Mail server accepts any address of the domain (catch-all)".into(),
+            UncertaintyReason::DeferredVerification => "Provider is known to accept addresses which bounce on delivery".into(),
+            UncertaintyReason::SenderRejected => "Mail server rejected all configured sender addresses".into(),
+            UncertaintyReason::Inconclusive => "None of the configured strategies could verify the address".into(),
+            UncertaintyReason::EaiUnsupported => "Mail server doesn't support internationalized addresses (SMTPUTF8)".into(),
+            UncertaintyReason::TlsRequired => "Mail server requires an encrypted connection (STARTTLS)".into(),
+            UncertaintyReason::PolicyRejection => "Mail server refused to verify the address due to its policy".into(),
+            UncertaintyReason::RelayDenied => "Mail server refused to relay mail to the domain. It is likely not responsible for the domain.".into(),
+            UncertaintyReason::VerificationUnsupported => "Provider is known to block address verification".into(),
+            UncertaintyReason::Conflicting => "Independent checks disagreed on whether the address exists".into(),
         };
 
         write!(f, "{message}")
     }
 }
 
+impl FailureReason {
+    /// Stable identifier of the reason, the variant name in snake case
+    /// (e.g. `"no_such_address"`), see [`CheckResult::reason_code`]
+    #[must_use]
+    pub const fn code(&self) -> &'static str {
+        match self {
+            FailureReason::InvalidAddressFormat => "invalid_address_format",
+            FailureReason::NoMxRecords => "no_mx_records",
+            FailureReason::InvalidMxRecords => "invalid_mx_records",
+            FailureReason::ParkedDomain => "parked_domain",
+            FailureReason::NoSuchAddress => "no_such_address",
+            FailureReason::IoError(_) => "io_error",
+            FailureReason::Risky(_) => "risky",
+        }
+    }
+}
+
 impl Display for FailureReason {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let message = match self {
@@ -134,8 +660,15 @@ impl Display for FailureReason {
                 "Invalid address format. Expected format: local-part@domain".into()
             }
             FailureReason::NoMxRecords => "No MX records found for domain".into(),
+            FailureReason::InvalidMxRecords => {
+                "MX records of the domain don't name valid mail servers".into()
+            }
+            FailureReason::ParkedDomain => {
+                "Domain has no MX records and no mail server at its address".into()
+            }
             FailureReason::NoSuchAddress => "Mail server rejects the address".into(),
             FailureReason::IoError(e) => format!("IO error: {e}"),
+            FailureReason::Risky(reason) => format!("Risky address: {reason}"),
         };
 
         write!(f, "{message}")
@@ -149,7 +682,18 @@ enum Error {
     Smtp(async_smtp::error::Error),
     Io(std::io::Error),
     NoMxRecords,
+    InvalidMxRecords,
+    ParkedDomain,
+    DnssecValidation,
     Timeout,
+    RecipientTimeout,
+    SenderRejected,
+    EaiUnsupported,
+    ClosedBeforeGreeting,
+    GreetingTimeout,
+    AllMxUnreachable,
+    Inconclusive,
+    VerificationUnsupported,
 }
 
 impl From<ResolveError> for Error {
@@ -172,21 +716,73 @@ impl From<async_smtp::error::Error> for Error {
 
 type Result<T = ()> = std::result::Result<T, Error>;
 
+/// Providers known to verify each mailbox honestly, see [`Config::trusted_providers`]
+pub const DEFAULT_TRUSTED_PROVIDERS: &[&str] = &[
+    "gmail.com",
+    "googlemail.com",
+    "protonmail.com",
+    "protonmail.ch",
+    "proton.me",
+    "pm.me",
+    "yandex.com",
+    "yandex.ru",
+    "tuta.com",
+    "tutanota.com",
+    "tutanota.de",
+    "tutao.de",
+];
+
+/// Providers known to accept any address when the recipient is announced,
+/// see [`Config::catch_all_providers`]
+pub const DEFAULT_CATCH_ALL_PROVIDERS: &[&str] = &["yahoo.com", "aol.com"];
+
+/// Providers known to block verification, see [`Config::unverifiable_providers`]
+pub const DEFAULT_UNVERIFIABLE_PROVIDERS: &[&str] = &["outlook.com", "hotmail.com", "bluewin.ch"];
+
 #[derive(Clone, Debug)]
-/// Customise the behaviour of email checking
+/// Customise the behaviour of email checking.
+///
+/// All durations are measured with Tokio's clock, so tests can control them
+/// with `tokio::time::pause` instead of waiting.
 pub struct Config {
     /// If a check exceeds the configured timeout duration
     /// it is aborted and resolves to [`CheckResult::Uncertain`] with [`UncertaintyReason::Timeout`].
+    /// If a mail server doesn't answer in time, the next mail server of the domain
+    /// is tried with the same timeout, see [`DetailedResult::used_fallback`].
     pub timeout: Option<Duration>,
 
-    /// Address used when preparing to send the mail. No mail is actually ever sent.
-    /// This value might be rejected by mail servers.
-    /// For example it can be rejected with the following reasons:
+    /// Delay of the greeting above which it counts as a block signal. Some anti-spam
+    /// servers delay their greeting by many seconds to trip up bots. Checks still proceed
+    /// after such a delay, but are flagged with [`DetailedResult::banner_delayed`].
+    /// If the [`Config::timeout`] passes while waiting for the greeting, the check resolves
+    /// to [`UncertaintyReason::Blocklisted`] instead of [`UncertaintyReason::Timeout`].
+    /// Disabled by default.
+    pub banner_delay_threshold: Option<Duration>,
+
+    /// Addresses used when preparing to send the mail. No mail is actually ever sent.
+    /// These values might be rejected by mail servers.
+    /// For example they can be rejected with the following reasons:
     ///
     /// - SPF rejection as per <https://www.rfc-editor.org/rfc/rfc7208>
     /// - 4.1.8 Sender address rejected (<https://www.suped.com/knowledge/email-deliverability/troubleshooting/what-does-smtp-bounce-reason-418-bad-senders-system-address-domain-of-sender-address-does-not-re>)
     /// - 5.7.27 Sender address has null MX (<https://www.rfc-editor.org/rfc/rfc7505#section-4.2>)
-    pub sender_address: EmailAddress,
+    ///
+    /// The [`Client`] picks the addresses round-robin. If a sender is rejected
+    /// the next one is tried, until all of them were rejected which results in
+    /// [`UncertaintyReason::SenderRejected`].
+    /// If empty, the null reverse-path (`MAIL FROM:<>`) is used.
+    pub sender_addresses: Vec<EmailAddress>,
+
+    /// Additional parameters of the `MAIL FROM` command, e.g. `BODY=7BIT`,
+    /// sent whether the server announced the corresponding extension or not.
+    /// If the server supports the `SIZE` extension, `SIZE=0` is sent unless
+    /// a [`MailParameter::Size`] is configured. Empty by default.
+    pub mail_parameters: Vec<MailParameter>,
+
+    /// Whether a `BODY` parameter is sent depending on the extensions the server advertises,
+    /// unless one is configured in [`Config::mail_parameters`].
+    /// Defaults to [`BodyParameter::Auto`].
+    pub body_parameter: BodyParameter,
 
     /// Client domain, used as parameter of the EHLO message.
     /// This value might be rejected by mail servers.
@@ -195,27 +791,416 @@ pub struct Config {
 
     /// Port to use to connect to the SMTP mail server
     pub port: u16,
+
+    /// Ports to use for specific mail server hosts, overriding [`Config::port`].
+    /// Keys are either host names (`mx.example.com`) or patterns with a leading
+    /// wildcard label (`*.example.com`), which match any subdomain.
+    /// Exact host names take precedence over patterns, and more specific
+    /// patterns over less specific ones. Matching ignores case and trailing dots.
+    pub ports: HashMap<String, u16>,
+
+    /// Ports tried in order if the mail server can't be connected to on [`Config::port`]
+    /// (or the port from [`Config::ports`]) or doesn't greet us, e.g. `[587]` for networks
+    /// blocking outgoing traffic on port 25. The connection is plaintext on every port.
+    /// Attempts are only bounded by [`Config::timeout`], so ports which silently drop
+    /// traffic may use up the whole timeout. Empty by default.
+    pub fallback_ports: Vec<u16>,
+
+    /// Maximum number of connections opened per check, across all mail servers
+    /// of the domain, [`Config::fallback_ports`] and the [`Config::double_check`].
+    /// Bounds the work spent on domains with many unreachable mail servers,
+    /// which would otherwise each get the full [`Config::timeout`].
+    /// Once exhausted, the result of the last attempt is reported.
+    /// Values below 1 are treated as 1. Unlimited by default.
+    pub max_connection_attempts: Option<usize>,
+
+    /// Mail servers to use for specific domains instead of looking up their MX records,
+    /// e.g. `example.com` → `127.0.0.1` to test against a local mail server
+    /// or in networks without DNS. Values are host names or IP addresses.
+    /// Domains match ignoring case and trailing dots. Empty by default.
+    pub mx_overrides: HashMap<String, String>,
+
+    /// Local address to connect to mail servers from, e.g. one of several addresses of the host.
+    /// Only addresses of the mail server with the same IP version are connected to.
+    /// If `None`, the operating system picks the address.
+    pub bind_addr: Option<IpAddr>,
+
+    /// Where the name we introduce ourselves with in EHLO comes from
+    pub client_domain_source: ClientDomainSource,
+
+    /// Whether domains without MX records are checked at their own address.
+    /// [`ImplicitMx::Disabled`] by default.
+    pub implicit_mx: ImplicitMx,
+
+    /// Maximum number of checks running at the same time
+    /// when checking multiple addresses, e.g. with [`Client::check_channel`].
+    /// Values below 1 are treated as 1.
+    pub concurrency: usize,
+
+    /// Maximum number of checks of addresses of the same domain running at the same time
+    /// when checking multiple addresses, to avoid being throttled by the mail server,
+    /// e.g. checking 500 addresses in parallel while only checking 4 at gmail.com.
+    /// Addresses waiting for their domain don't occupy one of the [`Config::concurrency`],
+    /// so other domains proceed meanwhile. Up to [`Config::concurrency`] addresses wait
+    /// at the same time, after which no further addresses are taken.
+    /// Values below 1 are treated as 1. Unlimited by default.
+    pub per_domain_concurrency: Option<usize>,
+
+    /// Random delay between starting two checks when checking multiple addresses.
+    /// Firing requests at machine speed is an obvious trigger for blocklisting,
+    /// randomized pacing makes the traffic look less automated.
+    /// Note that this limits the throughput to roughly one check per average delay,
+    /// regardless of [`Config::concurrency`]. Disabled by default.
+    pub jitter: Option<RangeInclusive<Duration>>,
+
+    /// Backend to fall back on when SMTP verification results in
+    /// [`UncertaintyReason::Blocklisted`], [`UncertaintyReason::Timeout`],
+    /// [`UncertaintyReason::RecipientTimeout`], [`UncertaintyReason::OutboundPort25Blocked`],
+    /// [`UncertaintyReason::AllMxUnreachable`] or [`UncertaintyReason::VerificationUnsupported`].
+    /// This makes it possible to use a third-party verification service
+    /// in networks where SMTP verification is impossible.
+    pub fallback: Option<Arc<dyn VerificationBackend>>,
+
+    /// Strategies used to verify the address, see [`StrategyChain`]
+    pub strategies: StrategyChain,
+
+    /// Commands issued besides those of the [`Config::strategies`].
+    /// [`CommandSequence::Minimal`] by default.
+    pub command_sequence: CommandSequence,
+
+    /// Whether the [`Config::strategies`] stop at the first definitive result.
+    /// [`ChainMode::ShortCircuit`] by default.
+    pub chain_mode: ChainMode,
+
+    /// Local part of the random recipient announced by [`Strategy::CatchAllProbe`].
+    pub probe_local_part: ProbeLocalPart,
+
+    /// Report accepted addresses of catch-all domains as [`UncertaintyReason::CatchAll`]
+    /// instead of [`CheckResult::Success`], as such an acceptance says little about
+    /// whether the mailbox exists. Only has an effect with [`Strategy::CatchAllProbe`].
+    /// Disabled by default.
+    pub demote_catch_all: bool,
+
+    /// Check the address once more after the given delay if the result is uncertain,
+    /// e.g. due to greylisting or a timeout, and combine both results with [`CheckResult::merge`].
+    /// The second check opens a new connection and may reach another mail server of the domain.
+    /// Definitive results aren't checked again, neither are uncertain ones which won't change
+    /// on a second attempt ([`UncertaintyReason::CatchAll`], [`UncertaintyReason::EaiUnsupported`],
+    /// [`UncertaintyReason::TlsRequired`], [`UncertaintyReason::Inconclusive`]
+    /// and [`UncertaintyReason::VerificationUnsupported`]),
+    /// so only uncertain checks take longer. The [`DetailedResult`] holds the diagnostic
+    /// information of the second check. Disabled by default.
+    pub double_check: Option<Duration>,
+
+    /// How uncertain results are resolved, applied to the final result of each check.
+    /// [`ResolutionProfile::Balanced`] by default.
+    pub resolution: ResolutionProfile,
+
+    /// Remove the subaddress of addresses at providers known to support subaddressing
+    /// before verifying, e.g. `john.doe+newsletter@gmail.com` is verified as `john.doe@gmail.com`.
+    /// Some servers reject tagged recipients although they would deliver to them.
+    /// Results are still reported for the original address. Disabled by default.
+    pub strip_subaddress: bool,
+
+    /// Whether a rejection indicating both blocklisting and an inexistent address
+    /// is reported as [`UncertaintyReason::Blocklisted`] (default) or [`FailureReason::NoSuchAddress`]
+    pub rejection_precedence: RejectionPrecedence,
+
+    /// Rules classifying the responses of mail servers, consulted in order
+    /// before the built-in heuristics. The first matching rule determines the result,
+    /// e.g. to report a provider's "550 5.7.1 Mailbox disabled" as [`FailureReason::NoSuchAddress`].
+    /// Only responses ending the check are classified, i.e. not responses to `VRFY`
+    /// or to a rejected sender which is retried with the next one. Empty by default.
+    pub classification_rules: Vec<ClassificationRule>,
+
+    /// Domains of providers which accept an address only if its mailbox exists.
+    /// For these, [`CheckResult::Success`] is marked as [`DetailedResult::authoritative`]
+    /// and the [`Strategy::CatchAllProbe`] is skipped.
+    /// Defaults to [`DEFAULT_TRUSTED_PROVIDERS`].
+    pub trusted_providers: Vec<String>,
+
+    /// Domains of providers which accept any address when the recipient is announced,
+    /// whether its mailbox exists or not. For these, [`CheckResult::Success`] is reported as
+    /// [`UncertaintyReason::CatchAll`] and the [`Strategy::CatchAllProbe`] is skipped.
+    /// Defaults to [`DEFAULT_CATCH_ALL_PROVIDERS`].
+    pub catch_all_providers: Vec<String>,
+
+    /// Domains of providers which accept addresses when the recipient is announced
+    /// but verify them only on delivery, bouncing undeliverable ones afterwards.
+    /// For these, [`CheckResult::Success`] is reported as [`UncertaintyReason::DeferredVerification`].
+    /// Empty by default.
+    pub deferring_providers: Vec<String>,
+
+    /// Domains of providers which block verification, e.g. by rejecting every request
+    /// from addresses without a reverse DNS entry. Addresses of these are reported as
+    /// [`UncertaintyReason::VerificationUnsupported`] right away, without connecting,
+    /// which saves time and avoids harming the reputation of our IP address.
+    /// Defaults to [`DEFAULT_UNVERIFIABLE_PROVIDERS`], clear it to verify them anyway.
+    pub unverifiable_providers: Vec<String>,
+
+    /// Open and park a connection to the mail server in [`Client::warm_up`].
+    /// Disabled by default, in which case only the mail server host is resolved.
+    pub park_connections: bool,
+
+    /// How long mail server hosts and connections prepared by [`Client::warm_up`]
+    /// remain usable. Mail servers should keep idle connections open for at least
+    /// 5 minutes as per [RFC5321 section 4.5.3.2.7](https://www.rfc-editor.org/rfc/rfc5321#section-4.5.3.2.7),
+    /// but many close them sooner. Defaults to 30 seconds.
+    pub warm_up_lifetime: Duration,
+
+    /// Interval of `NOOP` commands keeping connections parked by [`Client::warm_up`] open
+    /// until they are used, for servers closing idle connections sooner than the
+    /// [`Config::warm_up_lifetime`]. Unused connections are closed with `QUIT` once the
+    /// lifetime passed. Disabled by default, in which case parked connections aren't greeted
+    /// and idle silently.
+    pub keepalive_interval: Option<Duration>,
+
+    /// How mail servers are looked up, e.g. with DNS-over-TLS for privacy
+    pub dns: DnsConfig,
+
+    /// Limits on the responses of mail servers
+    pub response_limits: ResponseLimits,
+
+    /// Receives the progress of every check together with the checked address,
+    /// e.g. to drive a live display. Events are dropped if the channel is full,
+    /// so a slow receiver never holds up checks. Disabled by default.
+    pub events: Option<Sender<(String, CheckEvent)>>,
+
+    /// Cache the results of [`Client::check`] by address, so checking an address again
+    /// within the time to live returns the cached result without any network requests.
+    /// The cache is shared between clones of the [`Client`]. Detailed checks, e.g.
+    /// [`Client::check_detailed`], and [`Client::check_with`] bypass it.
+    /// Cached results can be removed with [`Client::invalidate_cached`]. Disabled by default.
+    pub result_cache: Option<CacheTtl>,
+}
+
+/// Where the name we introduce ourselves with in EHLO comes from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ClientDomainSource {
+    /// [`Config::client_domain`]
+    #[default]
+    Configured,
+    /// Reverse DNS name (PTR record) of [`Config::bind_addr`], so the name matches
+    /// the connecting address. Mail servers commonly distrust clients whose name doesn't
+    /// match their address. The name is looked up once per [`Client`].
+    /// Falls back on [`Config::client_domain`] if there is no `bind_addr` or the lookup fails.
+    Ptr,
+}
+
+/// `BODY` parameter of `MAIL FROM`, see [`Config::body_parameter`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BodyParameter {
+    /// Announce `BODY=8BITMIME` if the server advertises the `8BITMIME` extension as per
+    /// [RFC6152](https://www.rfc-editor.org/rfc/rfc6152), like modern clients do.
+    /// Some servers respond differently to clients which don't.
+    #[default]
+    Auto,
+    /// Only send a `BODY` parameter if one is configured in [`Config::mail_parameters`]
+    Omit,
+}
+
+/// Commands issued besides those of the strategies, see [`Config::command_sequence`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CommandSequence {
+    /// Only the commands required to verify the address
+    #[default]
+    Minimal,
+    /// Also issue `NOOP` after `EHLO` and end the transaction with `RSET`,
+    /// like a regular client would. Some servers distrust connections which
+    /// only announce recipients and disconnect. Responses to these commands are ignored.
+    Polite,
+}
+
+/// Whether domains without MX records are checked at their own address, see [`Config::implicit_mx`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ImplicitMx {
+    /// Report domains without MX records as [`FailureReason::NoMxRecords`]
+    #[default]
+    Disabled,
+    /// Connect to the A and AAAA records of domains without MX records as per
+    /// [RFC5321 section 5.1](https://www.rfc-editor.org/rfc/rfc5321#section-5.1).
+    ///
+    /// Parked domains and wildcard DNS setups resolve to addresses which usually
+    /// don't run a mail server. Such domains are reported as [`FailureReason::ParkedDomain`]
+    /// if all of their addresses are `parked_addresses` (e.g. those of a domain parking service),
+    /// or if none of their addresses accepts the connection and greets us.
+    Enabled { parked_addresses: Vec<IpAddr> },
+}
+
+/// Local part of the recipient announced to detect catch-all domains.
+///
+/// Some servers respond differently to obviously fake recipients than to plausible ones,
+/// and some blocklist senders probing with them. The probe must never collide with
+/// a real mailbox, otherwise the domain is wrongly detected as catch-all.
+/// Generated local parts therefore should always contain a random component.
+#[derive(Clone, Default)]
+pub enum ProbeLocalPart {
+    /// 32 random hexadecimal digits
+    #[default]
+    RandomHex,
+    /// The prefix followed by 12 random hexadecimal digits, e.g. `john.doe.` for `john.doe.3f9a0c12b7e4`
+    Prefix(String),
+    /// Local parts generated by the given function
+    Custom(Arc<dyn Fn() -> String + Send + Sync>),
+}
+
+impl ProbeLocalPart {
+    fn generate(&self) -> String {
+        let mut rng = rand::rng();
+        match self {
+            ProbeLocalPart::RandomHex => format!("{:032x}", rng.random::<u128>()),
+            ProbeLocalPart::Prefix(prefix) => {
+                format!("{prefix}{:012x}", rng.random::<u64>() >> 16)
+            }
+            ProbeLocalPart::Custom(generate) => generate(),
+        }
+    }
+}
+
+impl std::fmt::Debug for ProbeLocalPart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RandomHex => write!(f, "RandomHex"),
+            Self::Prefix(prefix) => f.debug_tuple("Prefix").field(prefix).finish(),
+            Self::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
 }
 
+#[derive(Debug, PartialEq, Eq)]
 pub enum ClientBuildError {
     InvalidEmailAddress,
+    /// The [`Config::client_domain`] is empty or contains whitespace or control characters
+    InvalidClientDomain,
+    /// A keyword of the [`Config::mail_parameters`] is empty or contains
+    /// whitespace, control characters or `=`
+    InvalidMailParameter,
+    /// The [`Config::dns`] requires a [`DnsUpstream`] with addresses
+    /// and, for encrypted transports, a TLS name
+    InvalidDnsConfig,
+    /// The DNS resolver couldn't be built, e.g. because the system's configuration
+    /// can't be read and there is no [`DnsConfig::fallback_upstream`]
+    ResolverUnavailable(String),
 }
 
 impl Config {
-    /// Set the sender address
+    /// Configuration identifying the operator towards mail server administrators,
+    /// so they can reach out instead of blocklisting.
+    ///
+    /// The `domain` must be a real domain under the operator's control,
+    /// ideally matching the reverse DNS entry of the IP address used to connect.
+    /// It's used as [`Config::client_domain`]. The `contact` address is used as the only
+    /// [`Config::sender_addresses`] and defaults to `postmaster@domain`, which mail domains
+    /// must provide as per [RFC5321 section 4.5.1](https://www.rfc-editor.org/rfc/rfc5321#section-4.5.1).
+    /// Checks are paced with a [`Config::concurrency`] of 2 and a [`Config::jitter`]
+    /// of 1 to 3 seconds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the domain isn't a fully-qualified domain name
+    /// or the contact address contains invalid characters
+    pub fn good_citizen(
+        domain: &str,
+        contact: Option<String>,
+    ) -> std::result::Result<Self, ClientBuildError> {
+        let valid_domain = Domain::new(domain)
+            .is_some_and(|d| !d.is_literal() && d.ascii().contains('.') && d.ascii().is_ascii());
+        if !valid_domain || domain.chars().any(|c| c.is_control() || c.is_whitespace()) {
+            return Err(ClientBuildError::InvalidClientDomain);
+        }
+
+        let domain = domain.trim_end_matches('.');
+        let contact = contact.unwrap_or_else(|| format!("postmaster@{domain}"));
+        Self {
+            concurrency: 2,
+            jitter: Some(Duration::from_secs(1)..=Duration::from_secs(3)),
+            ..Default::default()
+        }
+        .with_client_domain(format!("{domain}."))
+        .with_sender_address(contact)
+    }
+
+    /// Set a single sender address
     ///
     /// # Errors
     ///
     /// Returns an error if the address string contains invalid characters
     pub fn with_sender_address(
-        mut self,
+        self,
         address: String,
     ) -> std::result::Result<Self, ClientBuildError> {
-        self.sender_address =
-            EmailAddress::new(address).map_err(|_| ClientBuildError::InvalidEmailAddress)?;
+        self.with_sender_addresses(vec![address])
+    }
+
+    /// Set multiple sender addresses, see [`Config::sender_addresses`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the address strings contains invalid characters
+    pub fn with_sender_addresses(
+        mut self,
+        addresses: Vec<String>,
+    ) -> std::result::Result<Self, ClientBuildError> {
+        self.sender_addresses = addresses
+            .into_iter()
+            .map(EmailAddress::new)
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|_| ClientBuildError::InvalidEmailAddress)?;
         Ok(self)
     }
 
+    /// Mail server host to use for the domain, see [`Config::mx_overrides`]
+    fn mx_override(&self, domain: &Domain) -> Option<&str> {
+        self.mx_overrides
+            .iter()
+            .find(|(overridden, _)| domain.matches(overridden))
+            .map(|(_, host)| host.as_str())
+    }
+
+    /// Port to use for the given mail server host, see [`Config::ports`]
+    fn port_for(&self, host: &str) -> u16 {
+        if self.ports.is_empty() {
+            return self.port;
+        }
+
+        let host = host.trim_end_matches('.').to_lowercase();
+        let ports: HashMap<_, _> = self
+            .ports
+            .iter()
+            .map(|(pattern, port)| (pattern.trim_end_matches('.').to_lowercase(), *port))
+            .collect();
+
+        if let Some(port) = ports.get(&host) {
+            return *port;
+        }
+
+        // Try wildcard patterns from the most to the least specific
+        host.match_indices('.')
+            .find_map(|(index, _)| ports.get(&format!("*{}", &host[index..])))
+            .copied()
+            .unwrap_or(self.port)
+    }
+
+    /// Whether the domain belongs to one of the [`Config::trusted_providers`]
+    fn is_trusted(&self, domain: &Domain) -> bool {
+        is_provider(&self.trusted_providers, domain)
+    }
+
+    /// Whether the domain belongs to one of the [`Config::catch_all_providers`]
+    fn is_catch_all(&self, domain: &Domain) -> bool {
+        is_provider(&self.catch_all_providers, domain)
+    }
+
+    /// Whether the domain belongs to one of the [`Config::deferring_providers`]
+    fn is_deferring(&self, domain: &Domain) -> bool {
+        is_provider(&self.deferring_providers, domain)
+    }
+
+    /// Whether the domain belongs to one of the [`Config::unverifiable_providers`]
+    fn is_unverifiable(&self, domain: &Domain) -> bool {
+        is_provider(&self.unverifiable_providers, domain)
+    }
+
     /// Set the client domain
     #[must_use]
     pub fn with_client_domain(mut self, client_domain: String) -> Self {
@@ -228,159 +1213,2476 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             timeout: Some(Duration::from_secs(10)),
-            sender_address: EmailAddress::new("me@thomaszahner.ch".into()).unwrap(),
+            banner_delay_threshold: None,
+            sender_addresses: vec![EmailAddress::new("me@thomaszahner.ch".into()).unwrap()],
+            mail_parameters: vec![],
+            body_parameter: BodyParameter::default(),
             client_domain: ClientId::Domain("example.com.".into()),
             port: 25,
+            ports: HashMap::new(),
+            fallback_ports: vec![],
+            max_connection_attempts: None,
+            mx_overrides: HashMap::new(),
+            bind_addr: None,
+            client_domain_source: ClientDomainSource::default(),
+            implicit_mx: ImplicitMx::default(),
+            concurrency: 10,
+            per_domain_concurrency: None,
+            jitter: None,
+            fallback: None,
+            strategies: StrategyChain::default(),
+            command_sequence: CommandSequence::default(),
+            chain_mode: ChainMode::default(),
+            probe_local_part: ProbeLocalPart::default(),
+            demote_catch_all: false,
+            double_check: None,
+            resolution: ResolutionProfile::default(),
+            strip_subaddress: false,
+            rejection_precedence: RejectionPrecedence::default(),
+            classification_rules: vec![],
+            trusted_providers: DEFAULT_TRUSTED_PROVIDERS
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            catch_all_providers: DEFAULT_CATCH_ALL_PROVIDERS
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            deferring_providers: vec![],
+            unverifiable_providers: DEFAULT_UNVERIFIABLE_PROVIDERS
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            park_connections: false,
+            warm_up_lifetime: Duration::from_secs(30),
+            keepalive_interval: None,
+            dns: DnsConfig::default(),
+            response_limits: ResponseLimits::default(),
+            events: None,
+            result_cache: None,
         }
     }
 }
 
-#[derive(Default, Debug, Clone)]
+fn is_provider(providers: &[String], domain: &Domain) -> bool {
+    providers.iter().any(|provider| domain.matches(provider))
+}
+
+#[derive(Debug, Clone)]
 pub struct Client {
     config: Config,
+    /// Built once from [`Config::dns`], so its cache is shared between checks and clones
+    resolver: TokioResolver,
+    /// Round-robin position in [`Config::sender_addresses`], shared between clones
+    next_sender: Arc<AtomicUsize>,
+    /// Prepared by [`Client::warm_up`], shared between clones
+    pool: Arc<Pool>,
+    /// Connection failures across checks, shared between clones
+    connectivity: Arc<Connectivity>,
+    /// Looked up once for [`ClientDomainSource::Ptr`], shared between clones
+    ptr_client_domain: Arc<OnceCell<ClientId>>,
+    /// See [`Config::result_cache`], shared between clones
+    cache: Arc<ResultCache>,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self::new(Config::default()).expect("the default configuration is valid")
+    }
 }
 
 impl Client {
-    #[must_use]
-    pub const fn new(config: Config) -> Self {
-        Self { config }
+    /// Create a client with the given configuration
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the [`Config::client_domain`] can't be sent to mail servers as is,
+    /// if the [`Config::dns`] is incomplete or if the DNS resolver can't be built
+    pub fn new(config: Config) -> std::result::Result<Self, ClientBuildError> {
+        if let ClientId::Domain(domain) = &config.client_domain
+            && (domain.is_empty() || domain.chars().any(|c| c.is_control() || c.is_whitespace()))
+        {
+            return Err(ClientBuildError::InvalidClientDomain);
+        }
+
+        let invalid_keyword = |keyword: &str| {
+            keyword.is_empty() || keyword.contains('=') || !smtp::is_valid_argument(keyword)
+        };
+        if config.mail_parameters.iter().any(|parameter| {
+            matches!(parameter, MailParameter::Other { keyword, .. } if invalid_keyword(keyword))
+        }) {
+            return Err(ClientBuildError::InvalidMailParameter);
+        }
+
+        if !config.dns.is_valid() {
+            return Err(ClientBuildError::InvalidDnsConfig);
+        }
+
+        let resolver = dns::resolver(&config.dns)
+            .map_err(|e| ClientBuildError::ResolverUnavailable(e.to_string()))?;
+
+        Ok(Self {
+            config,
+            resolver,
+            next_sender: Arc::default(),
+            pool: Arc::default(),
+            connectivity: Arc::default(),
+            ptr_client_domain: Arc::default(),
+            cache: Arc::default(),
+        })
     }
 
     /// Check if the given email address exists
     /// and is setup to receive messages, without sending
     /// a message.
     pub async fn check(&self, address: &str) -> CheckResult {
-        check_inner(address, &self.config).await.into()
+        // Invalid addresses fail without network requests, so they aren't cached
+        let (Some(ttl), Some(key)) = (&self.config.result_cache, cache_key(address)) else {
+            return self.check_detailed(address).await.result;
+        };
+        if let Some(result) = self.cache.get(&key) {
+            return result;
+        }
+
+        let result = self.check_detailed(address).await.result;
+        self.cache.insert(&key, result.clone(), ttl);
+        result
     }
-}
 
-async fn check_inner(mail: &str, config: &Config) -> Result {
-    let host = get_host(mail).await?;
+    /// Remove the cached result of the address, see [`Config::result_cache`],
+    /// e.g. after the user confirmed it, so the next check verifies it again
+    pub fn invalidate_cached(&self, address: &str) {
+        if let Some(key) = cache_key(address) {
+            self.cache.invalidate(&key);
+        }
+    }
 
-    let future = verify_mail(mail, &host, config);
+    /// Like [`Client::check`], but taking the client and the address by value,
+    /// so the future is `'static` and can be spawned as a detached task,
+    /// e.g. in a server handler. Clones of the client share its state.
+    pub async fn into_check(self, address: String) -> CheckResult {
+        self.check(&address).await
+    }
 
-    if let Some(timeout) = config.timeout {
-        time::timeout(timeout, future)
-            .await
-            .map_err(|_| Error::Timeout)?
-    } else {
-        future.await
+    /// Like [`Client::check`] but with additional diagnostic information
+    pub async fn check_detailed(&self, address: &str) -> DetailedResult {
+        self.check_session(address, Session::default()).await
     }
-}
 
-/// Roughly follows [RFC5322 section 3.4.1](https://www.rfc-editor.org/rfc/rfc5322#section-3.4.1).
-/// The RFC isn't followed strictly because this is not a format validation library.
-/// Encoding the RFC's full complexity would require additional effort, like supporting comments
-/// (CFWS) which isn't really used in the real world today.
-async fn get_host(mail: &str) -> Result<Name> {
-    let (local_part, domain) = mail.rsplit_once('@').ok_or(Error::InvalidAddressFormat)?;
+    /// Like [`Client::check`] but with some of the configuration changed for this check only,
+    /// e.g. a different sender. Unlike a new [`Client`], this keeps the DNS cache,
+    /// the connections prepared by [`Client::warm_up`] and the other shared state.
+    /// See [`ConfigOverrides`] for the fields which can be overridden.
+    pub async fn check_with(&self, address: &str, overrides: ConfigOverrides) -> CheckResult {
+        // Results of other configurations must not be mixed up with cached ones
+        let client = Self {
+            config: Config {
+                result_cache: None,
+                ..overrides.apply(&self.config)
+            },
+            ..self.clone()
+        };
+        client.check(address).await
+    }
 
-    // Handle surrounding FWS. Note that we don't handle CFWS.
-    let local_part = local_part.trim();
-    let domain = domain.trim();
+    /// Like [`Client::check_detailed`] but verifies through the given mail server
+    /// (e.g. `gmail-smtp-in.l.google.com`) instead of the preferred mail server of the domain.
+    /// Useful to reproduce issues tied to a particular mail server.
+    /// An invalid host name results in [`FailureReason::InvalidAddressFormat`].
+    pub async fn check_via(&self, address: &str, mx_host: &str) -> DetailedResult {
+        let session = Session {
+            mx_host: Some(mx_host.to_string()),
+            ..Default::default()
+        };
+        self.check_session(address, session).await
+    }
 
-    if local_part.is_empty() || domain.is_empty() {
-        return Err(Error::InvalidAddressFormat);
+    /// Verify the address against every mail server of its domain independently,
+    /// in order of preference, e.g. to find a misconfigured mail server.
+    /// Each check is like [`Client::check_via`], the hosts are fully-qualified.
+    ///
+    /// # Errors
+    ///
+    /// Returns the result of the failed lookup if the address is invalid
+    /// or the mail servers of the domain can't be resolved
+    pub async fn check_all_mx(
+        &self,
+        address: &str,
+    ) -> std::result::Result<Vec<(String, CheckResult)>, CheckResult> {
+        let ParsedAddress { domain, .. } = parse_address(address).map_err(CheckResult::Failure)?;
+        let hosts = get_hosts(&domain, &self.config, &self.resolver)
+            .await
+            .map_err(|e| {
+                check_result(
+                    Err(e),
+                    self.config.rejection_precedence,
+                    &self.config.classification_rules,
+                )
+            })?;
+
+        let mut results = Vec::with_capacity(hosts.len());
+        for MailServer { host, .. } in hosts {
+            let host = host_name(&host);
+            let result = self.check_via(address, &host).await.result;
+            results.push((host, result));
+        }
+        Ok(results)
     }
 
-    if let Some(domain_literal) = domain.strip_prefix("[").and_then(|d| d.strip_suffix("]")) {
-        // No DNS lookup with domain literals
-        Name::from_str(domain_literal).map_err(|_| Error::InvalidAddressFormat)
-    } else {
-        let record = first_dns_record(domain).await?;
-        Ok(record.exchange().clone())
+    /// Check the address, and once more if the result is uncertain, see [`Config::double_check`]
+    async fn check_session(&self, address: &str, session: Session) -> DetailedResult {
+        let mx_host = session.mx_host.clone();
+        let mut detailed = self.check_once(address, session).await;
+
+        if let Some(delay) = self.config.double_check
+            && worth_double_checking(&detailed.result)
+        {
+            let session = Session {
+                mx_host,
+                connection_attempts: detailed.connection_attempts,
+                ..Default::default()
+            };
+            if session.attempts_exhausted(&self.config) {
+                return self.complete(address, detailed);
+            }
+            time::sleep(delay).await;
+            let mut second = self.check_once(address, session).await;
+            let result = std::mem::replace(&mut second.result, CheckResult::Success);
+            second.result = detailed.result.merge(result);
+            detailed = second;
+        }
+        self.complete(address, detailed)
     }
-}
 
-async fn verify_mail(mail: &str, host: &Name, config: &Config) -> Result {
-    let port = config.port;
+    /// Report the final result of the check to [`Config::events`], if configured
+    fn complete(&self, address: &str, detailed: DetailedResult) -> DetailedResult {
+        if let Some(sender) = &self.config.events {
+            Emitter::new(sender.clone(), address)
+                .emit(CheckEvent::Completed(detailed.result.clone()));
+        }
+        detailed
+    }
 
-    let stream = BufStream::new(TcpStream::connect(format!("{host}:{port}")).await?);
-    let client = SmtpClient::new();
-    let mut transport = SmtpTransport::new(client, stream).await?;
+    async fn check_once(&self, address: &str, mut session: Session) -> DetailedResult {
+        let start = Instant::now();
+        session.sender_offset = self.next_sender.fetch_add(1, Ordering::Relaxed);
+        session.events = self
+            .config
+            .events
+            .clone()
+            .map(|sender| Emitter::new(sender, address));
+        session.client_domain = self.ptr_client_domain().await;
+        let outcome = check_inner(
+            address,
+            &self.config,
+            &self.resolver,
+            &self.pool,
+            &mut session,
+        )
+        .await;
+        session.finish_phase();
+        let response = match &outcome {
+            Err(Error::Smtp(
+                async_smtp::error::Error::Transient(response)
+                | async_smtp::error::Error::Permanent(response),
+            )) => Some(response),
+            _ => None,
+        };
+        let blocklist_evidence = response.and_then(heuristics::blocklist_evidence);
+        let retry_after = response.and_then(heuristics::retry_after);
+
+        let connected = session.phase > Some(Phase::Connect);
+        let connect_failed = session.phase == Some(Phase::Connect) && outcome.is_err();
+        let blocked = match &session.domain {
+            Some(domain) if connected || connect_failed => {
+                self.connectivity.record(domain, connected)
+            }
+            _ => false,
+        };
 
-    transport
-        .get_mut()
-        .ehlo(config.client_domain.clone())
-        .await?;
+        let mut result = self.classify(outcome, &session);
+        if blocked && connect_failed {
+            result = CheckResult::Uncertain(UncertaintyReason::OutboundPort25Blocked);
+        }
+        let blocklist_evidence = blocklist_evidence
+            .filter(|_| result == CheckResult::Uncertain(UncertaintyReason::Blocklisted));
 
-    transport
-        .get_mut()
-        .command(MailCommand::new(
-            Some(config.sender_address.clone()),
-            vec![],
-        ))
-        .await?;
+        if result == CheckResult::Success {
+            result = self.provider_acceptance(&mut session);
+        }
 
-    let mail = EmailAddress::new(mail.into()).map_err(|_| Error::InvalidAddressFormat)?;
-    transport
-        .get_mut()
-        .command(RcptCommand::new(mail, vec![]))
-        .await?;
+        if let Some(fallback) = &self.config.fallback
+            && matches!(
+                result,
+                CheckResult::Uncertain(
+                    UncertaintyReason::Blocklisted
+                        | UncertaintyReason::Timeout
+                        | UncertaintyReason::RecipientTimeout
+                        | UncertaintyReason::OutboundPort25Blocked
+                        | UncertaintyReason::AllMxUnreachable
+                        | UncertaintyReason::VerificationUnsupported
+                )
+            )
+        {
+            result = fallback.verify(address).await;
+        }
 
-    Ok(())
-}
+        if self.config.demote_catch_all
+            && session.catch_all == Some(true)
+            && result == CheckResult::Success
+        {
+            result = CheckResult::Uncertain(UncertaintyReason::CatchAll);
+        }
 
-/// Get MX record with the highest preference
-async fn first_dns_record(domain: &str) -> Result<MX> {
-    lookup_mx(domain)
-        .await?
-        .first()
-        .cloned()
-        .ok_or(Error::NoMxRecords)
+        let result = self.config.resolution.resolve(result);
+
+        let authoritative = result == CheckResult::Success
+            && session
+                .domain
+                .as_ref()
+                .is_some_and(|domain| self.config.is_trusted(domain));
+
+        DetailedResult {
+            result,
+            domain: session.domain,
+            phase: session.phase,
+            port: session.port,
+            peer_addr: session.peer_addr,
+            mx_count: session.mx_count,
+            mx_preference: session.mx_preference,
+            used_fallback: session.used_fallback,
+            banner_delayed: session.banner_delayed,
+            connection_attempts: session.connection_attempts,
+            strategy_results: session.strategy_results,
+            catch_all: session.catch_all,
+            blocklist_evidence,
+            authoritative,
+            retry_after,
+            timings: Timings {
+                total: start.elapsed(),
+                ..session.timings
+            },
+            transcript: session.transcript.lines(),
+        }
+    }
 }
 
-/// Get all usable MX records, sorted by preference.
-/// Returns only non-root FQDN records.
-async fn lookup_mx(domain: &str) -> Result<Vec<MX>> {
-    let mut records: Vec<_> = hickory_resolver::Resolver::builder_tokio()?
-        .build()
-        .mx_lookup(domain)
-        .await?
-        .into_iter()
-        // Only resolvable, fully-qualified domain names (FQDNs) are permitted when domain names are used in SMTP.
-        // Source: https://datatracker.ietf.org/doc/html/rfc5321#section-2.3.5
-        .filter(|r| r.exchange().is_fqdn())
-        .filter(|r| !r.exchange().is_root()) // trying to connect "." will always fail
-        .collect();
+impl Client {
+    /// Classify the outcome of the check. With [`ChainMode::Exhaustive`]
+    /// the results of the strategies are merged.
+    fn classify(&self, outcome: Result, session: &Session) -> CheckResult {
+        let merged = session
+            .strategy_results
+            .iter()
+            .map(|(_, result)| result.clone())
+            .reduce(CheckResult::merge);
+
+        match (outcome, merged) {
+            (Ok(()), Some(merged)) => merged,
+            (outcome, _) => check_result(
+                outcome,
+                self.config.rejection_precedence,
+                &self.config.classification_rules,
+            ),
+        }
+    }
 
-    records.sort_by_key(MX::preference);
+    /// What an accepted address of the domain means, see [`Config::catch_all_providers`]
+    /// and [`Config::deferring_providers`]
+    fn provider_acceptance(&self, session: &mut Session) -> CheckResult {
+        match &session.domain {
+            Some(domain) if self.config.is_catch_all(domain) => {
+                session.catch_all = Some(true);
+                CheckResult::Uncertain(UncertaintyReason::CatchAll)
+            }
+            Some(domain) if self.config.is_deferring(domain) => {
+                CheckResult::Uncertain(UncertaintyReason::DeferredVerification)
+            }
+            _ => CheckResult::Success,
+        }
+    }
 
-    Ok(records)
+    /// Name to introduce ourselves with, see [`ClientDomainSource::Ptr`]
+    async fn ptr_client_domain(&self) -> Option<ClientId> {
+        let bind_addr = self
+            .config
+            .bind_addr
+            .filter(|_| self.config.client_domain_source == ClientDomainSource::Ptr)?;
+
+        self.ptr_client_domain
+            .get_or_try_init(|| async {
+                dns::ptr_name(bind_addr, &self.resolver)
+                    .await
+                    .map(ClientId::Domain)
+                    .ok_or(())
+            })
+            .await
+            .ok()
+            .cloned()
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use std::time::Duration;
+/// Whether checking again might make the result more definitive, see [`Config::double_check`]
+fn worth_double_checking(result: &CheckResult) -> bool {
+    matches!(result, CheckResult::Uncertain(reason) if !matches!(
+        reason,
+        UncertaintyReason::CatchAll
+            | UncertaintyReason::DeferredVerification
+            | UncertaintyReason::EaiUnsupported
+            | UncertaintyReason::TlsRequired
+            | UncertaintyReason::Inconclusive
+            | UncertaintyReason::VerificationUnsupported
+    ))
+}
 
-    use crate::{CheckResult, Client, Config, FailureReason, UncertaintyReason};
+/// State of a single check, collecting diagnostic information along the way
+#[derive(Default)]
+struct Session {
+    /// Position in [`Config::sender_addresses`] to start with
+    sender_offset: usize,
+    /// Mail server to use instead of the one of the domain
+    mx_host: Option<String>,
+    /// Name to introduce ourselves with instead of [`Config::client_domain`]
+    client_domain: Option<ClientId>,
+    domain: Option<Domain>,
+    phase: Option<Phase>,
+    /// Start of the current phase
+    phase_start: Option<Instant>,
+    timings: Timings,
+    catch_all: Option<bool>,
+    /// Port of the established connection
+    port: Option<u16>,
+    /// Address of the established connection
+    peer_addr: Option<SocketAddr>,
+    mx_count: usize,
+    /// Preference of the mail server which answered
+    mx_preference: Option<u16>,
+    used_fallback: bool,
+    /// Whether a greeting exceeded the [`Config::banner_delay_threshold`]
+    banner_delayed: bool,
+    /// Connections opened so far, see [`Config::max_connection_attempts`]
+    connection_attempts: usize,
+    strategy_results: Vec<(Strategy, CheckResult)>,
+    transcript: Transcript,
+    events: Option<Emitter>,
+}
 
-    async fn check(address: &str) -> CheckResult {
-        Client::default().check(address).await
+impl Session {
+    /// Emit the event to [`Config::events`], if configured
+    fn emit(&self, event: CheckEvent) {
+        if let Some(events) = &self.events {
+            events.emit(event);
+        }
     }
 
-    #[tokio::test]
-    async fn invalid_format() {
-        let expected = CheckResult::Failure(FailureReason::InvalidAddressFormat);
-        assert_eq!(check("some text").await, expected);
-        assert_eq!(check("@").await, expected);
-        assert_eq!(check("local-part@").await, expected);
-        assert_eq!(check("@domain").await, expected);
+    /// Whether no more connections may be opened, see [`Config::max_connection_attempts`]
+    fn attempts_exhausted(&self, config: &Config) -> bool {
+        config
+            .max_connection_attempts
+            .is_some_and(|max| self.connection_attempts >= max.max(1))
     }
 
-    #[tokio::test]
-    async fn timeout() {
-        let result = Client::new(Config {
-            timeout: Some(Duration::ZERO),
-            ..Default::default()
-        })
-        .check("a@gmail.com")
-        .await;
+    fn enter(&mut self, phase: Phase) {
+        self.finish_phase();
+        self.phase = Some(phase);
+        self.phase_start = Some(Instant::now());
+    }
 
-        assert_eq!(result, CheckResult::Uncertain(UncertaintyReason::Timeout));
+    /// Attribute the time since entering the current phase to it
+    fn finish_phase(&mut self) {
+        if let (Some(phase), Some(start)) = (self.phase, self.phase_start.take()) {
+            self.timings.add(phase, start.elapsed());
+        }
     }
+}
 
-    #[tokio::test]
+async fn check_inner(
+    mail: &str,
+    config: &Config,
+    resolver: &TokioResolver,
+    pool: &Pool,
+    session: &mut Session,
+) -> Result {
+    let ParsedAddress {
+        local_part, domain, ..
+    } = parse_address(mail).map_err(|_| Error::InvalidAddressFormat)?;
+    session.domain = Some(domain.clone());
+    if config.is_unverifiable(&domain) {
+        return Err(Error::VerificationUnsupported);
+    }
+
+    session.enter(Phase::Dns);
+    session.emit(CheckEvent::Resolving);
+    let servers = if let Some(mx_host) = &session.mx_host {
+        let host = mail_host(mx_host)?;
+        vec![MailServer {
+            host,
+            preference: None,
+            implicit: false,
+        }]
+    } else if let Some(servers) = pool.take_hosts(&domain, config.warm_up_lifetime) {
+        servers
+    } else {
+        get_hosts(&domain, config, resolver).await?
+    };
+    session.mx_count = servers
+        .iter()
+        .filter(|server| server.preference.is_some())
+        .count();
+
+    let local_part = if config.strip_subaddress {
+        subaddress::strip(&local_part, &domain)
+    } else {
+        &local_part
+    };
+
+    let result = verify_servers(local_part, &domain, &servers, config, pool, session).await;
+
+    // Parked domains commonly resolve to addresses which don't run a mail server
+    let unreachable = result.is_err() && session.phase <= Some(Phase::Greeting);
+    if unreachable && servers.iter().all(|server| server.implicit) {
+        return Err(Error::ParkedDomain);
+    }
+    result
+}
+
+/// Verify the address with the first mail server which can be reached, in the given order.
+/// As per [RFC5321 section 5.1](https://www.rfc-editor.org/rfc/rfc5321#section-5.1)
+/// the next mail server is tried if connecting or waiting for the greeting fails.
+/// It is also tried if the mail server refuses to relay, see [`UncertaintyReason::RelayDenied`].
+/// If several MX records were looked up and none of their mail servers accepted a connection,
+/// this results in [`UncertaintyReason::AllMxUnreachable`].
+/// Each mail server gets the full [`Config::timeout`].
+async fn verify_servers(
+    local_part: &str,
+    domain: &Domain,
+    servers: &[MailServer],
+    config: &Config,
+    pool: &Pool,
+    session: &mut Session,
+) -> Result {
+    use async_smtp::error::Error::Permanent;
+
+    let mut result = Err(Error::NoMxRecords);
+    let mut unreachable = 0;
+    // Phase in which a server refused to relay, which is reported unless another one is reached
+    let mut relay_denied = None;
+    for (attempt, server) in servers.iter().enumerate() {
+        if attempt > 0 && session.attempts_exhausted(config) {
+            break;
+        }
+        let outcome =
+            verify_with_timeout(local_part, domain, &server.host, config, pool, session).await;
+        let reached = outcome.is_ok() || session.phase > Some(Phase::Greeting);
+        if !reached {
+            if session.phase == Some(Phase::Connect) {
+                unreachable += 1;
+            }
+            if relay_denied.is_none() {
+                result = outcome;
+            }
+            continue;
+        }
+
+        session.mx_preference = server.preference;
+        session.used_fallback = attempt > 0;
+        // A server refusing to relay isn't responsible for the domain, another one might be
+        relay_denied =
+            matches!(&outcome, Err(Error::Smtp(Permanent(r))) if heuristics::relay_denied(r))
+                .then_some(session.phase);
+        result = outcome;
+        if relay_denied.is_none() {
+            break;
+        }
+    }
+
+    if let Some(phase) = relay_denied
+        && session.phase != phase
+    {
+        // Describe the server which denied relaying rather than the unreachable ones after it
+        session.finish_phase();
+        session.phase = phase;
+    }
+
+    let looked_up = servers
+        .iter()
+        .all(|server| server.preference.is_some() && !server.implicit);
+    if looked_up && unreachable > 1 && unreachable == servers.len() {
+        return Err(Error::AllMxUnreachable);
+    }
+    result
+}
+
+async fn verify_with_timeout(
+    local_part: &str,
+    domain: &Domain,
+    host: &Name,
+    config: &Config,
+    pool: &Pool,
+    session: &mut Session,
+) -> Result {
+    let future = verify_mail(local_part, domain, host, config, pool, session);
+
+    let Some(timeout) = config.timeout else {
+        return future.await;
+    };
+
+    match time::timeout(timeout, future).await {
+        Ok(result) => result,
+        Err(_) if session.phase == Some(Phase::Rcpt) => Err(Error::RecipientTimeout),
+        Err(_)
+            if session.phase == Some(Phase::Greeting)
+                && config.banner_delay_threshold.is_some() =>
+        {
+            Err(Error::GreetingTimeout)
+        }
+        // The address was already accepted, only the probe is inconclusive
+        Err(_) if session.phase == Some(Phase::CatchAllProbe) => Ok(()),
+        Err(_) => Err(Error::Timeout),
+    }
+}
+
+/// Mail server of a domain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct MailServer {
+    host: Name,
+    /// Preference of the MX record. `None` if the host wasn't looked up, e.g. for domain literals.
+    preference: Option<u16>,
+    /// Whether this is an address of the domain itself, see [`ImplicitMx`]
+    implicit: bool,
+}
+
+/// Name of a mail server given as a host name or an IP address.
+/// IPv6 addresses aren't valid names, so they are kept as a single raw label,
+/// see [`host_name`].
+fn mail_host(host: &str) -> Result<Name> {
+    match host.parse::<Ipv6Addr>() {
+        Ok(ip) => Name::from_labels([ip.to_string().as_bytes()]),
+        Err(_) => Name::from_str(host),
+    }
+    .map_err(|_| Error::InvalidAddressFormat)
+}
+
+/// The mail server as given to [`mail_host`], e.g. to connect to it or to look up its port
+fn host_name(host: &Name) -> String {
+    let mut labels = host.iter();
+    if let (Some(label), None) = (labels.next(), labels.next())
+        && let Some(ip) = std::str::from_utf8(label)
+            .ok()
+            .and_then(|label| label.parse::<Ipv6Addr>().ok())
+    {
+        return ip.to_string();
+    }
+    host.to_string()
+}
+
+/// Get all mail servers for the given domain, sorted by preference.
+/// Never empty.
+async fn get_hosts(
+    domain: &Domain,
+    config: &Config,
+    resolver: &TokioResolver,
+) -> Result<Vec<MailServer>> {
+    // No DNS lookup with domain literals and overridden domains
+    let host = match domain.ip() {
+        Some(ip) => Some(mail_host(&ip.to_string())?),
+        None => config.mx_override(domain).map(mail_host).transpose()?,
+    };
+    if let Some(host) = host {
+        return Ok(vec![MailServer {
+            host,
+            preference: None,
+            implicit: false,
+        }]);
+    }
+
+    let name = dns::query_name(domain, &config.dns);
+    let records = match lookup_mx(&name, resolver, config.dns.dnssec).await {
+        Err(Error::DnsResolution(e))
+            if e.is_no_records_found() && config.implicit_mx != ImplicitMx::Disabled =>
+        {
+            vec![]
+        }
+        records => records?,
+    };
+    let hosts: Vec<_> = records
+        .into_iter()
+        .map(|record| MailServer {
+            host: record.exchange().clone(),
+            preference: Some(record.preference()),
+            implicit: false,
+        })
+        .collect();
+
+    if hosts.is_empty() {
+        return implicit_mx(domain, config, resolver).await;
+    }
+    Ok(hosts)
+}
+
+/// Addresses of the domain itself for domains without MX records, see [`ImplicitMx`]
+async fn implicit_mx(
+    domain: &Domain,
+    config: &Config,
+    resolver: &TokioResolver,
+) -> Result<Vec<MailServer>> {
+    let ImplicitMx::Enabled { parked_addresses } = &config.implicit_mx else {
+        return Err(Error::NoMxRecords);
+    };
+    let Ok(lookup) = resolver
+        .lookup_ip(dns::query_name(domain, &config.dns))
+        .await
+    else {
+        return Err(Error::NoMxRecords);
+    };
+
+    let mut addresses: Vec<IpAddr> = lookup.iter().collect();
+    addresses.dedup();
+    if addresses.is_empty() {
+        return Err(Error::NoMxRecords);
+    }
+    if addresses
+        .iter()
+        .all(|address| parked_addresses.contains(address))
+    {
+        return Err(Error::ParkedDomain);
+    }
+
+    // Connect to the resolved addresses rather than resolving the domain once more
+    let hosts: Vec<_> = addresses
+        .iter()
+        .filter_map(|address| mail_host(&address.to_string()).ok())
+        .map(|host| MailServer {
+            host,
+            preference: None,
+            implicit: true,
+        })
+        .collect();
+    if hosts.is_empty() {
+        return Err(Error::NoMxRecords);
+    }
+    Ok(hosts)
+}
+
+/// Key of the address in the [`Config::result_cache`], so that different spellings
+/// of the same address (e.g. `mailto:` URIs or the case of the domain) share their result
+fn cache_key(address: &str) -> Option<String> {
+    let parsed = parse_address(address).ok()?;
+    Some(format!("{}@{}", parsed.local_part, parsed.domain.ascii()))
+}
+
+/// Stream of an SMTP connection
+type Stream = BufStream<Recorder<TcpStream>>;
+
+async fn verify_mail(
+    local_part: &str,
+    domain: &Domain,
+    host: &Name,
+    config: &Config,
+    pool: &Pool,
+    session: &mut Session,
+) -> Result {
+    let mut ports = vec![config.port_for(&host_name(host))];
+    for port in &config.fallback_ports {
+        if !ports.contains(port) {
+            ports.push(*port);
+        }
+    }
+
+    let mut attempt = 0;
+    let mut connection = loop {
+        let port = ports[attempt];
+        match connect(host, port, config, pool, session).await {
+            Ok(connection) => {
+                session.port = Some(port);
+                break connection;
+            }
+            Err(_) if attempt + 1 < ports.len() && !session.attempts_exhausted(config) => {
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    };
+
+    session.enter(Phase::Hello);
+    session.emit(CheckEvent::Ehlo);
+    let client_domain = session
+        .client_domain
+        .as_ref()
+        .unwrap_or(&config.client_domain);
+    let capabilities = hello(&mut connection, client_domain).await?;
+    if config.command_sequence == CommandSequence::Polite {
+        let _ = connection.command(NoopCommand).await;
+    }
+
+    let mut parameters = mail_parameters(config, &capabilities);
+
+    // The domain is always sent in its ASCII form, only a non-ASCII local part requires SMTPUTF8
+    let internationalized = !local_part.is_ascii();
+    if internationalized {
+        if !smtp::supports(&capabilities, "SMTPUTF8") {
+            return Err(Error::EaiUnsupported);
+        }
+        if !parameters.contains(&MailParameter::SmtpUtfEight) {
+            parameters.push(MailParameter::SmtpUtfEight);
+        }
+    }
+
+    session.enter(Phase::MailFrom);
+    mail_from(
+        &mut connection,
+        &config.sender_addresses,
+        session.sender_offset,
+        &parameters,
+    )
+    .await?;
+
+    let mail = smtp::mailbox(local_part, domain).ok_or(Error::InvalidAddressFormat)?;
+    let accepted = run_strategies(&mut connection, &mail, domain, config, session).await;
+
+    // Also after a rejection, so that we don't disconnect with a recipient announced
+    if config.command_sequence == CommandSequence::Polite {
+        let _ = connection.command(RsetCommand).await;
+    }
+
+    // With recorded results the final result is merged from them, see `Client::classify`
+    if accepted? || !session.strategy_results.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Inconclusive)
+    }
+}
+
+/// Run the [`Config::strategies`] and return whether the address was accepted
+async fn run_strategies(
+    connection: &mut Connection<Stream>,
+    mail: &str,
+    domain: &Domain,
+    config: &Config,
+    session: &mut Session,
+) -> Result<bool> {
+    let mut accepted = false;
+    let exhaustive = config.chain_mode == ChainMode::Exhaustive;
+
+    for strategy in &config.strategies.0 {
+        match strategy {
+            Strategy::Vrfy if !accepted || exhaustive => {
+                session.enter(Phase::Vrfy);
+                let outcome = vrfy(connection, mail, config.rejection_precedence).await;
+                accepted |= record(*strategy, outcome, config, session)?;
+            }
+            Strategy::Rcpt if !accepted || exhaustive => {
+                session.enter(Phase::Rcpt);
+                session.emit(CheckEvent::RcptSent);
+                let outcome = rcpt(connection, mail.to_string()).await.map(|()| true);
+                accepted |= record(*strategy, outcome, config, session)?;
+            }
+            Strategy::CatchAllProbe
+                if accepted && !config.is_trusted(domain) && !config.is_catch_all(domain) =>
+            {
+                session.enter(Phase::CatchAllProbe);
+                let local_part = config.probe_local_part.generate();
+                session.catch_all = probe_catch_all(connection, &local_part, domain, config).await;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(accepted)
+}
+
+/// Parameters of `MAIL FROM` for the extensions the server advertised in its EHLO response
+fn mail_parameters(config: &Config, capabilities: &Response) -> Vec<MailParameter> {
+    let mut parameters = config.mail_parameters.clone();
+
+    // We never send a message, so announcing its size as 0 avoids size related rejections
+    let announces_size = parameters
+        .iter()
+        .any(|parameter| matches!(parameter, MailParameter::Size(_)));
+    if !announces_size && smtp::size_limit(capabilities).is_some() {
+        parameters.push(MailParameter::Size(0));
+    }
+
+    let announces_body = parameters
+        .iter()
+        .any(|parameter| matches!(parameter, MailParameter::Body(_)));
+    if !announces_body
+        && config.body_parameter == BodyParameter::Auto
+        && smtp::supports(capabilities, "8BITMIME")
+    {
+        parameters.push(MailParameter::Body(MailBodyParameter::EightBitMime));
+    }
+
+    parameters
+}
+
+/// Whether the strategy accepted the address. With [`ChainMode::Exhaustive`] rejections
+/// don't end the check but are recorded, see [`DetailedResult::strategy_results`].
+fn record(
+    strategy: Strategy,
+    outcome: Result<bool>,
+    config: &Config,
+    session: &mut Session,
+) -> Result<bool> {
+    use async_smtp::error::Error::{Permanent, Transient};
+
+    if config.chain_mode != ChainMode::Exhaustive {
+        return outcome;
+    }
+    let result = match outcome {
+        Ok(true) => CheckResult::Success,
+        Ok(false) => CheckResult::Uncertain(UncertaintyReason::Inconclusive),
+        Err(error @ Error::Smtp(Transient(_) | Permanent(_))) => check_result(
+            Err(error),
+            config.rejection_precedence,
+            &config.classification_rules,
+        ),
+        Err(error) => return Err(error),
+    };
+    let accepted = result == CheckResult::Success;
+    session.strategy_results.push((strategy, result));
+    Ok(accepted)
+}
+
+async fn rcpt(connection: &mut Connection<Stream>, mail: String) -> Result {
+    connection.command(RecipientCommand::new(mail)).await?;
+    Ok(())
+}
+
+/// Ask the server if the address exists with VRFY.
+/// Returns whether the address was confirmed. Rejections of the address are returned as error,
+/// other negative responses (e.g. VRFY being disabled) as `false`.
+async fn vrfy(
+    connection: &mut Connection<Stream>,
+    mail: &str,
+    precedence: RejectionPrecedence,
+) -> Result<bool> {
+    use async_smtp::error::Error::{Permanent, Transient};
+
+    match connection.command(VrfyCommand::new(mail.to_string())).await {
+        // 252 Cannot VRFY user, but will accept message and attempt delivery
+        Ok(response) => Ok(response.has_code(250) || response.has_code(251)),
+        Err(Permanent(r)) => match heuristics::handle_permanent(r.clone(), precedence) {
+            CheckResult::Failure(_) => Err(Permanent(r).into()),
+            _ => Ok(false),
+        },
+        Err(Transient(_)) => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Connect to the mail server on the given port and wait for its greeting
+async fn connect(
+    host: &Name,
+    port: u16,
+    config: &Config,
+    pool: &Pool,
+    session: &mut Session,
+) -> Result<Connection<Stream>> {
+    session.enter(Phase::Connect);
+    session.connection_attempts += 1;
+    session.emit(CheckEvent::Connecting {
+        host: host_name(host),
+        port,
+    });
+    let stream = match pool
+        .take_connection(host, port, config.warm_up_lifetime)
+        .await
+    {
+        // The greeting of a kept alive connection was already read
+        Some(Reused::Greeted(Greeted {
+            mut connection,
+            peer_addr,
+        })) => {
+            connection
+                .stream_mut()
+                .get_mut()
+                .record_into(&session.transcript);
+            session.peer_addr = peer_addr;
+            return Ok(connection);
+        }
+        // The greeting of a parked connection is still waiting to be read
+        Some(Reused::Fresh(stream)) => stream,
+        None => tcp_connect(host, port, config.bind_addr).await?,
+    };
+    let peer_addr = stream.peer_addr().ok();
+    let stream = BufStream::new(Recorder::new(stream, session.transcript.clone()));
+    let mut connection = Connection::new(stream, config.response_limits);
+
+    session.enter(Phase::Greeting);
+    let start = Instant::now();
+    connection.read_greeting().await.map_err(|e| match e {
+        async_smtp::error::Error::Io(e) if closed_by_server(&e) => Error::ClosedBeforeGreeting,
+        e => Error::from(e),
+    })?;
+    if config
+        .banner_delay_threshold
+        .is_some_and(|threshold| start.elapsed() > threshold)
+    {
+        session.banner_delayed = true;
+    }
+
+    session.peer_addr = peer_addr;
+    Ok(connection)
+}
+
+/// Open a TCP connection to the host, from the local address if given
+async fn tcp_connect(
+    host: &Name,
+    port: u16,
+    bind_addr: Option<IpAddr>,
+) -> std::io::Result<TcpStream> {
+    let host = host_name(host);
+    let Some(bind_addr) = bind_addr else {
+        return TcpStream::connect((host.as_str(), port)).await;
+    };
+
+    let mut error = None;
+    for address in lookup_host((host.as_str(), port))
+        .await?
+        .filter(|address| address.is_ipv4() == bind_addr.is_ipv4())
+    {
+        let socket = if bind_addr.is_ipv4() {
+            TcpSocket::new_v4()?
+        } else {
+            TcpSocket::new_v6()?
+        };
+        socket.bind(SocketAddr::new(bind_addr, 0))?;
+        match socket.connect(address).await {
+            Ok(stream) => return Ok(stream),
+            Err(e) => error = Some(e),
+        }
+    }
+
+    Err(error.unwrap_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::AddrNotAvailable,
+            format!("{host} has no address of the IP version of {bind_addr}"),
+        )
+    }))
+}
+
+/// Whether the server closed or reset the connection
+fn closed_by_server(error: &std::io::Error) -> bool {
+    use std::io::ErrorKind::{ConnectionAborted, ConnectionReset, UnexpectedEof};
+    matches!(
+        error.kind(),
+        UnexpectedEof | ConnectionReset | ConnectionAborted
+    )
+}
+
+/// Announce a random recipient of the domain, which almost certainly doesn't exist.
+/// Returns `None` if the response is inconclusive.
+async fn probe_catch_all(
+    connection: &mut Connection<Stream>,
+    local_part: &str,
+    domain: &Domain,
+    config: &Config,
+) -> Option<bool> {
+    // Sent exactly like the actual recipient.
+    // A custom local part might not be valid, in which case nothing can be probed.
+    let probe = smtp::mailbox(local_part, domain)?;
+    let result = rcpt(connection, probe).await;
+
+    // Classified like the response to the actual recipient
+    match check_result(
+        result,
+        config.rejection_precedence,
+        &config.classification_rules,
+    ) {
+        CheckResult::Success => Some(true),
+        CheckResult::Failure(_) => Some(false),
+        CheckResult::Uncertain(_) => None,
+    }
+}
+
+/// Greet the server with EHLO. Servers not supporting ESMTP reject EHLO
+/// with 500 or 502, in which case we fall back to HELO as per
+/// [RFC5321 section 3.2](https://www.rfc-editor.org/rfc/rfc5321#section-3.2).
+async fn hello(connection: &mut Connection<Stream>, client_id: &ClientId) -> Result<Response> {
+    match connection
+        .command(EhloCommand::new(client_id.clone()))
+        .await
+    {
+        Err(async_smtp::error::Error::Permanent(r)) if r.has_code(500) || r.has_code(502) => {
+            Ok(connection.command(HeloCommand(client_id.clone())).await?)
+        }
+        result => Ok(result?),
+    }
+}
+
+/// Issue the `MAIL FROM` command, starting with the sender at `offset`.
+/// Whenever a sender is rejected the transaction is reset and the next one is tried.
+async fn mail_from(
+    connection: &mut Connection<Stream>,
+    senders: &[EmailAddress],
+    offset: usize,
+    parameters: &[MailParameter],
+) -> Result {
+    use async_smtp::error::Error::{Permanent, Transient};
+
+    if senders.is_empty() {
+        connection
+            .command(MailCommand::new(None, parameters.to_vec()))
+            .await?;
+        return Ok(());
+    }
+
+    for (attempt, sender) in senders
+        .iter()
+        .cycle()
+        .skip(offset % senders.len())
+        .take(senders.len())
+        .enumerate()
+    {
+        if attempt > 0 {
+            connection.command(RsetCommand).await?;
+        }
+
+        match connection
+            .command(MailCommand::new(Some(sender.clone()), parameters.to_vec()))
+            .await
+        {
+            Ok(_) => return Ok(()),
+            Err(Transient(r) | Permanent(r)) if heuristics::sender_rejected(&r) => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Err(Error::SenderRejected)
+}
+
+/// Get all usable MX records, sorted by preference.
+/// Returns only non-root FQDN records.
+///
+/// Aliases are followed by the resolver: for a domain below a DNAME as per
+/// [RFC6672](https://www.rfc-editor.org/rfc/rfc6672), the server synthesizes a CNAME
+/// to the redirected domain, whose MX records are returned. Only MX records are collected,
+/// the DNAME and CNAME records of the chain are skipped.
+async fn lookup_mx(domain: &str, resolver: &TokioResolver, dnssec: bool) -> Result<Vec<MX>> {
+    let lookup = resolver.mx_lookup(domain).await?;
+
+    // Unsigned domains are insecure but not bogus
+    if dnssec
+        && lookup
+            .as_lookup()
+            .record_iter()
+            .any(|record| record.proof().is_bogus())
+    {
+        return Err(Error::DnssecValidation);
+    }
+
+    let records: Vec<_> = lookup.into_iter().collect();
+    // A null MX declares that the domain accepts no mail as per RFC7505,
+    // so the domain itself isn't an implicit MX either
+    if !records.is_empty() && records.iter().all(|r| r.exchange().is_root()) {
+        return Err(Error::NoMxRecords);
+    }
+    let records: Vec<_> = records
+        .into_iter()
+        .filter(|r| !r.exchange().is_root()) // trying to connect "." will always fail
+        .collect();
+    let valid: Vec<_> = records
+        .iter()
+        .filter(|r| is_valid_exchange(r.exchange()))
+        .cloned()
+        .collect();
+
+    // Distinguish misconfigured domains from domains without mail servers
+    if valid.is_empty() && !records.is_empty() {
+        return Err(Error::InvalidMxRecords);
+    }
+
+    Ok(sort_by_preference(valid, &mut rand::rng()))
+}
+
+/// Whether the exchange of an MX record is a host name we can connect to.
+/// Only resolvable, fully-qualified domain names (FQDNs) are permitted when domain names are used in SMTP
+/// as per [RFC5321 section 2.3.5](https://www.rfc-editor.org/rfc/rfc5321#section-2.3.5),
+/// whose labels consist of letters, digits and inner hyphens as per
+/// [RFC1123 section 2.1](https://www.rfc-editor.org/rfc/rfc1123#section-2.1).
+fn is_valid_exchange(exchange: &Name) -> bool {
+    exchange.is_fqdn()
+        && exchange.iter().all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with(b"-")
+                && !label.ends_with(b"-")
+                && label
+                    .iter()
+                    .all(|c| c.is_ascii_alphanumeric() || *c == b'-')
+        })
+}
+
+/// Sort MX records by preference. Records with equal preference are shuffled
+/// to spread the load as per [RFC5321 section 5.1](https://www.rfc-editor.org/rfc/rfc5321#section-5.1).
+fn sort_by_preference(mut records: Vec<MX>, rng: &mut impl Rng) -> Vec<MX> {
+    records.shuffle(rng);
+    records.sort_by_key(MX::preference); // stable, so the order within a preference stays random
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{HashMap, HashSet},
+        net::{Ipv4Addr, Ipv6Addr},
+        str::FromStr,
+        time::Duration,
+    };
+
+    use async_smtp::{
+        EmailAddress,
+        extension::{ClientId, MailParameter},
+    };
+    use hickory_resolver::{
+        Name,
+        proto::{
+            rr::{
+                RData, Record, RecordType,
+                rdata::{A, CNAME, MX, NULL, PTR, TXT},
+            },
+            serialize::binary::BinEncodable,
+        },
+    };
+    use rand::{Rng, SeedableRng, rngs::StdRng, seq::IndexedRandom};
+
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+    };
+
+    use crate::{
+        CacheTtl, CheckEvent, CheckResult, Client, ClientBuildError, ClientDomainSource, Config,
+        DnsConfig, Domain, Error, FailureReason, ImplicitMx, MailServer, Phase,
+        RejectionPrecedence, SearchDomains, SenderDomainHealth, Session, UncertaintyReason,
+        check_result, dns, get_hosts,
+        mock::{mock_dns, serve_session, serve_smtp},
+        parse_address,
+        pool::Pool,
+        sort_by_preference, verify_servers,
+    };
+
+    async fn check(address: &str) -> CheckResult {
+        Client::default().check(address).await
+    }
+
+    #[test]
+    fn port_for_host() {
+        let config = Config {
+            port: 25,
+            ports: HashMap::from([
+                ("staging.example.com".into(), 2525),
+                ("*.example.com".into(), 587),
+                ("*.eu.example.com.".into(), 465),
+                ("MX.Example.ORG".into(), 26),
+            ]),
+            ..Default::default()
+        };
+
+        assert_eq!(config.port_for("staging.example.com."), 2525);
+        assert_eq!(config.port_for("mx1.example.com."), 587);
+        assert_eq!(config.port_for("mx.eu.example.com."), 465);
+        assert_eq!(config.port_for("a.b.eu.example.com"), 465);
+        assert_eq!(config.port_for("mx.example.org."), 26);
+        assert_eq!(config.port_for("example.com."), 25);
+        assert_eq!(config.port_for("mx.example.net."), 25);
+        assert_eq!(Config::default().port_for("mx.example.com."), 25);
+    }
+
+    #[test]
+    fn predicates() {
+        let success = CheckResult::Success;
+        let uncertain = CheckResult::Uncertain(UncertaintyReason::Timeout);
+        let failure = CheckResult::Failure(FailureReason::NoSuchAddress);
+
+        assert!(success.is_deliverable());
+        assert!(!success.is_undeliverable());
+        assert!(!success.is_indeterminate());
+
+        assert!(!uncertain.is_deliverable());
+        assert!(!uncertain.is_undeliverable());
+        assert!(uncertain.is_indeterminate());
+
+        assert!(!failure.is_deliverable());
+        assert!(failure.is_undeliverable());
+        assert!(!failure.is_indeterminate());
+    }
+
+    #[test]
+    fn mx_preference_ties() {
+        let mx = |preference, host| MX::new(preference, Name::from_str(host).unwrap());
+        let records = vec![
+            mx(20, "backup.example."),
+            mx(10, "a.example."),
+            mx(10, "b.example."),
+            mx(10, "c.example."),
+        ];
+
+        let mut first_hosts = HashSet::new();
+        for seed in 0..32 {
+            let sorted = sort_by_preference(records.clone(), &mut StdRng::seed_from_u64(seed));
+            assert_eq!(sorted[3], records[0]);
+            first_hosts.insert(sorted[0].exchange().to_string());
+        }
+
+        assert_eq!(first_hosts.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn invalid_format() {
+        let expected = CheckResult::Failure(FailureReason::InvalidAddressFormat);
+        assert_eq!(check("some text").await, expected);
+        assert_eq!(check("@").await, expected);
+        assert_eq!(check("local-part@").await, expected);
+        assert_eq!(check("@domain").await, expected);
+    }
+
+    #[tokio::test]
+    async fn random_input_never_panics() {
+        const CHARACTERS: &[char] = &[
+            'a', 'Z', '0', '.', '-', '@', '@', '[', ']', ':', '"', '\\', ' ', '\r', '\n', '\0',
+            '<', '>', 'ü', '🦀', '\u{200b}',
+        ];
+        let mut rng = StdRng::seed_from_u64(0);
+        let client = Client::new(Config {
+            port: 1, // Nothing should be listening
+            ..Default::default()
+        })
+        .unwrap();
+
+        for _ in 0..10_000 {
+            let length = rng.random_range(0..32);
+            let input: String = (0..length)
+                .map(|_| *CHARACTERS.choose(&mut rng).unwrap())
+                .collect();
+
+            // Skip inputs which would result in DNS lookups
+            if parse_address(&input).is_ok_and(|address| !address.is_ip_literal) {
+                continue;
+            }
+
+            let _ = client.check(&input).await;
+        }
+    }
+
+    #[test]
+    fn invalid_client_domain() {
+        for domain in ["", "example.com\r\nRSET", "example com"] {
+            let config = Config::default().with_client_domain(domain.into());
+            assert_eq!(
+                Client::new(config).unwrap_err(),
+                ClientBuildError::InvalidClientDomain
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_mail_parameter() {
+        for keyword in ["", "X=1", "X\r\nRSET"] {
+            let config = Config {
+                mail_parameters: vec![MailParameter::Other {
+                    keyword: keyword.into(),
+                    value: None,
+                }],
+                ..Default::default()
+            };
+            assert_eq!(
+                Client::new(config).unwrap_err(),
+                ClientBuildError::InvalidMailParameter
+            );
+        }
+    }
+
+    #[test]
+    fn good_citizen() {
+        let config = Config::good_citizen("verifier.example", None).unwrap();
+        assert_eq!(
+            config.client_domain,
+            ClientId::Domain("verifier.example.".into())
+        );
+        assert_eq!(
+            config.sender_addresses,
+            [EmailAddress::new("postmaster@verifier.example".into()).unwrap()]
+        );
+        assert!(config.jitter.is_some());
+
+        let config =
+            Config::good_citizen("verifier.example.", Some("abuse@verifier.example".into()))
+                .unwrap();
+        assert_eq!(
+            config.sender_addresses,
+            [EmailAddress::new("abuse@verifier.example".into()).unwrap()]
+        );
+
+        for domain in ["", "localhost", "[127.0.0.1]", "verifier example"] {
+            assert_eq!(
+                Config::good_citizen(domain, None).unwrap_err(),
+                ClientBuildError::InvalidClientDomain
+            );
+        }
+        assert_eq!(
+            Config::good_citizen("verifier.example", Some("abuse @verifier.example".into()))
+                .unwrap_err(),
+            ClientBuildError::InvalidEmailAddress
+        );
+    }
+
+    #[tokio::test]
+    async fn dname_redirect() {
+        let name = |name| Name::from_ascii(name).unwrap();
+        let dname = RData::Unknown {
+            code: RecordType::Unknown(39),
+            rdata: NULL::with(name("target.example.").to_bytes().unwrap()),
+        };
+        let answers = vec![
+            Record::from_rdata(name("alias.example."), 300, dname),
+            // Synthesized from the DNAME
+            Record::from_rdata(
+                name("mail.alias.example."),
+                300,
+                RData::CNAME(CNAME(name("mail.target.example."))),
+            ),
+            Record::from_rdata(
+                name("mail.target.example."),
+                300,
+                RData::MX(MX::new(10, name("mx.target.example."))),
+            ),
+        ];
+
+        let domain = Domain::new("mail.alias.example").unwrap();
+        let config = Config {
+            dns: mock_dns(answers),
+            ..Default::default()
+        };
+        let resolver = crate::dns::resolver(&config.dns).unwrap();
+        let hosts = get_hosts(&domain, &config, &resolver).await.unwrap();
+
+        assert_eq!(hosts[0].host, name("mx.target.example."));
+        assert_eq!(hosts[0].preference, Some(10));
+    }
+
+    #[tokio::test]
+    async fn malformed_mx() {
+        let mx = |preference, host| {
+            Record::from_rdata(
+                Name::from_ascii("example.com.").unwrap(),
+                300,
+                RData::MX(MX::new(preference, Name::from_ascii(host).unwrap())),
+            )
+        };
+        let lookup = |answers| async move {
+            let config = Config {
+                dns: mock_dns(answers),
+                ..Default::default()
+            };
+            let resolver = crate::dns::resolver(&config.dns).unwrap();
+            let domain = Domain::new("example.com").unwrap();
+            get_hosts(&domain, &config, &resolver).await
+        };
+
+        let hosts = lookup(vec![
+            mx(10, "mail_server.example.com."),
+            mx(20, "_mx.example.com."),
+            mx(30, "mx.example.com."),
+        ])
+        .await
+        .unwrap();
+        assert_eq!(hosts.len(), 1);
+        assert_eq!(hosts[0].host, Name::from_ascii("mx.example.com.").unwrap());
+
+        let result = lookup(vec![mx(10, "mail_server.example.com.")]).await;
+        assert_eq!(
+            CheckResult::from(result.map(|_| ())),
+            CheckResult::Failure(FailureReason::InvalidMxRecords)
+        );
+    }
+
+    #[test]
+    fn reason_codes() {
+        use CheckResult::{Failure, Success, Uncertain};
+
+        let response = "554 Go away\r\n".parse().unwrap();
+        let codes = [
+            (Success, "success"),
+            (Uncertain(UncertaintyReason::Timeout), "timeout"),
+            (
+                Uncertain(UncertaintyReason::AllMxUnreachable),
+                "all_mx_unreachable",
+            ),
+            (
+                Uncertain(UncertaintyReason::OutboundPort25Blocked),
+                "outbound_port25_blocked",
+            ),
+            (
+                Uncertain(UncertaintyReason::RecipientTimeout),
+                "recipient_timeout",
+            ),
+            (Uncertain(UncertaintyReason::Blocklisted), "blocklisted"),
+            (
+                Uncertain(UncertaintyReason::NoReverseHostname),
+                "no_reverse_hostname",
+            ),
+            (Uncertain(UncertaintyReason::Greylisted), "greylisted"),
+            (Uncertain(UncertaintyReason::Throttled), "throttled"),
+            (Uncertain(UncertaintyReason::ServerError), "server_error"),
+            (
+                Uncertain(UncertaintyReason::ProtocolError),
+                "protocol_error",
+            ),
+            (
+                Uncertain(UncertaintyReason::MessageSizeExceeded),
+                "message_size_exceeded",
+            ),
+            (Uncertain(UncertaintyReason::CatchAll), "catch_all"),
+            (
+                Uncertain(UncertaintyReason::DeferredVerification),
+                "deferred_verification",
+            ),
+            (Uncertain(UncertaintyReason::Inconclusive), "inconclusive"),
+            (
+                Uncertain(UncertaintyReason::SenderRejected),
+                "sender_rejected",
+            ),
+            (
+                Uncertain(UncertaintyReason::EaiUnsupported),
+                "eai_unsupported",
+            ),
+            (Uncertain(UncertaintyReason::TlsRequired), "tls_required"),
+            (
+                Uncertain(UncertaintyReason::PolicyRejection),
+                "policy_rejection",
+            ),
+            (Uncertain(UncertaintyReason::RelayDenied), "relay_denied"),
+            (
+                Uncertain(UncertaintyReason::VerificationUnsupported),
+                "verification_unsupported",
+            ),
+            (Uncertain(UncertaintyReason::Conflicting), "conflicting"),
+            (
+                Uncertain(UncertaintyReason::NegativeSmtpResponse(response)),
+                "negative_smtp_response",
+            ),
+            (
+                Uncertain(UncertaintyReason::SmtpError(String::new())),
+                "smtp_error",
+            ),
+            (
+                Uncertain(UncertaintyReason::DnsResolverError(String::new())),
+                "dns_resolver_error",
+            ),
+            (
+                Failure(FailureReason::InvalidAddressFormat),
+                "invalid_address_format",
+            ),
+            (Failure(FailureReason::NoMxRecords), "no_mx_records"),
+            (
+                Failure(FailureReason::InvalidMxRecords),
+                "invalid_mx_records",
+            ),
+            (Failure(FailureReason::ParkedDomain), "parked_domain"),
+            (Failure(FailureReason::NoSuchAddress), "no_such_address"),
+            (Failure(FailureReason::IoError(String::new())), "io_error"),
+            (
+                Failure(FailureReason::Risky(UncertaintyReason::CatchAll)),
+                "risky",
+            ),
+        ];
+
+        for (result, code) in codes {
+            assert_eq!(result.reason_code(), code);
+        }
+    }
+
+    #[test]
+    fn merge() {
+        use CheckResult::{Failure, Success, Uncertain};
+
+        let no_such_address = || Failure(FailureReason::NoSuchAddress);
+        let timeout = || Uncertain(UncertaintyReason::Timeout);
+        let blocklisted = || Uncertain(UncertaintyReason::Blocklisted);
+        let catch_all = || Uncertain(UncertaintyReason::CatchAll);
+        let conflicting = || Uncertain(UncertaintyReason::Conflicting);
+
+        assert_eq!(Success.merge(Success), Success);
+        assert_eq!(Success.merge(timeout()), Success);
+        assert_eq!(blocklisted().merge(no_such_address()), no_such_address());
+        assert_eq!(
+            no_such_address().merge(Failure(FailureReason::NoMxRecords)),
+            no_such_address()
+        );
+
+        // Contradicting results
+        assert_eq!(Success.merge(no_such_address()), conflicting());
+        assert_eq!(no_such_address().merge(Success), conflicting());
+        assert_eq!(
+            Success.merge(Failure(FailureReason::InvalidAddressFormat)),
+            Failure(FailureReason::InvalidAddressFormat)
+        );
+
+        assert_eq!(timeout().merge(blocklisted()), blocklisted());
+        assert_eq!(blocklisted().merge(catch_all()), catch_all());
+        assert_eq!(catch_all().merge(conflicting()), conflicting());
+        assert_eq!(conflicting().merge(timeout()), conflicting());
+        assert_eq!(
+            blocklisted().merge(Uncertain(UncertaintyReason::PolicyRejection)),
+            blocklisted()
+        );
+    }
+
+    #[test]
+    fn hash() {
+        use CheckResult::{Failure, Success, Uncertain};
+
+        let negative = |response: &str| {
+            Uncertain(UncertaintyReason::NegativeSmtpResponse(
+                response.parse().unwrap(),
+            ))
+        };
+        let results = [
+            Success,
+            Success,
+            Failure(FailureReason::NoSuchAddress),
+            Failure(FailureReason::Risky(UncertaintyReason::CatchAll)),
+            Uncertain(UncertaintyReason::CatchAll),
+            Uncertain(UncertaintyReason::DnsResolverError("timed out".into())),
+            Uncertain(UncertaintyReason::DnsResolverError("refused".into())),
+            negative("550 Go away\r\n"),
+            negative("550 Go away\r\n"),
+            negative("550 Not now\r\n"),
+            negative("450 Go away\r\n"),
+        ];
+
+        let distinct: HashSet<_> = results.iter().collect();
+        assert_eq!(distinct.len(), 9);
+
+        let mut tally = HashMap::new();
+        for result in results {
+            *tally.entry(result).or_insert(0) += 1;
+        }
+        assert_eq!(tally[&Success], 2);
+        assert_eq!(tally[&negative("550 Go away\r\n")], 2);
+        assert_eq!(tally[&negative("450 Go away\r\n")], 1);
+    }
+
+    #[test]
+    fn worst() {
+        use CheckResult::{Failure, Success, Uncertain};
+
+        let timeout = || Uncertain(UncertaintyReason::Timeout);
+        let blocklisted = || Uncertain(UncertaintyReason::Blocklisted);
+
+        assert_eq!(CheckResult::worst([]), Success);
+        assert_eq!(CheckResult::worst([Success, Success]), Success);
+        assert_eq!(
+            CheckResult::worst([Success, timeout(), blocklisted()]),
+            timeout()
+        );
+        assert_eq!(
+            CheckResult::worst(vec![
+                timeout(),
+                Success,
+                Failure(FailureReason::NoSuchAddress),
+                Failure(FailureReason::NoMxRecords),
+            ]),
+            Failure(FailureReason::NoSuchAddress)
+        );
+        let results = std::iter::repeat_n(Success, 3).chain([blocklisted()]);
+        assert_eq!(CheckResult::worst(results), blocklisted());
+    }
+
+    #[tokio::test]
+    async fn exists() {
+        assert_eq!(CheckResult::Success.exists(), Some(true));
+        assert_eq!(
+            CheckResult::Failure(FailureReason::NoSuchAddress).exists(),
+            Some(false)
+        );
+        assert_eq!(
+            CheckResult::Uncertain(UncertaintyReason::Greylisted).exists(),
+            None
+        );
+        assert_eq!(super::exists("not an address").await, Some(false));
+    }
+
+    #[test]
+    fn retryable() {
+        let negative = |response: &str| {
+            CheckResult::Uncertain(UncertaintyReason::NegativeSmtpResponse(
+                response.parse().unwrap(),
+            ))
+        };
+
+        assert!(negative("451 Please try again\r\n").is_retryable());
+        assert!(!negative("554 Go away\r\n").is_retryable());
+        assert!(CheckResult::Uncertain(UncertaintyReason::Greylisted).is_retryable());
+        assert!(!CheckResult::Uncertain(UncertaintyReason::Blocklisted).is_retryable());
+        assert!(!CheckResult::Success.is_retryable());
+        assert!(!CheckResult::Failure(FailureReason::NoSuchAddress).is_retryable());
+    }
+
+    #[tokio::test]
+    async fn result_cache() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = Client::new(Config {
+            port: listener.local_addr().unwrap().port(),
+            result_cache: Some(CacheTtl::default()),
+            ..Default::default()
+        })
+        .unwrap();
+        // Only a single session is served, so later checks can't reach the mail server
+        let smtp_server = tokio::spawn(serve_smtp(listener));
+
+        assert_eq!(client.check("a@[127.0.0.1]").await, CheckResult::Success);
+        smtp_server.await.unwrap();
+        assert_eq!(
+            client.clone().check("a@[127.0.0.1]").await,
+            CheckResult::Success
+        );
+        // Other spellings of the address share its result
+        assert_eq!(
+            client.check("mailto:a@[127.0.0.1]").await,
+            CheckResult::Success
+        );
+
+        client.invalidate_cached(" mailto:a@[127.0.0.1]");
+        assert!(matches!(
+            client.check("a@[127.0.0.1]").await,
+            CheckResult::Failure(FailureReason::IoError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn into_check() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = Client::new(Config {
+            port: listener.local_addr().unwrap().port(),
+            ..Default::default()
+        })
+        .unwrap();
+        let smtp_server = tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                tokio::spawn(serve_session(stream));
+            }
+        });
+
+        let checks: Vec<_> = (0..20)
+            .map(|i| tokio::spawn(client.clone().into_check(format!("user{i}@[127.0.0.1]"))))
+            .collect();
+        for check in checks {
+            assert_eq!(check.await.unwrap(), CheckResult::Success);
+        }
+        smtp_server.abort();
+    }
+
+    #[tokio::test]
+    async fn sender_domain_health() {
+        async fn health(answers: Vec<Record>) -> SenderDomainHealth {
+            let client = Client::new(Config {
+                dns: mock_dns(answers),
+                ..Default::default()
+            })
+            .unwrap();
+            client.sender_domain_health("example.com").await.unwrap()
+        }
+        let txt = |name, text: &[&str]| {
+            Record::from_rdata(
+                Name::from_ascii(name).unwrap(),
+                300,
+                RData::TXT(TXT::new(text.iter().map(ToString::to_string).collect())),
+            )
+        };
+
+        let configured = health(vec![
+            txt("example.com.", &["v=spf1 ip4:192.0.2.0/24", " -all"]),
+            txt("example.com.", &["google-site-verification=abc"]),
+            txt("_dmarc.example.com.", &["v=DMARC1; p=reject"]),
+        ])
+        .await;
+        assert_eq!(configured.spf, ["v=spf1 ip4:192.0.2.0/24 -all"]);
+        assert_eq!(configured.dmarc, ["v=DMARC1; p=reject"]);
+        assert!(configured.passes_basic_checks());
+
+        let unconfigured = health(vec![]).await;
+        assert!(unconfigured.spf.is_empty());
+        assert!(unconfigured.dmarc.is_empty());
+        assert!(!unconfigured.passes_basic_checks());
+
+        assert_eq!(
+            Client::default().sender_domain_health("[127.0.0.1]").await,
+            Err(CheckResult::Failure(FailureReason::InvalidAddressFormat))
+        );
+    }
+
+    #[tokio::test]
+    async fn suggest_parent_domain() {
+        let mx = |name| {
+            Record::from_rdata(
+                Name::from_ascii(name).unwrap(),
+                300,
+                RData::MX(MX::new(10, Name::from_ascii("mx.example.com.").unwrap())),
+            )
+        };
+        let answers = vec![
+            mx("example.com."),
+            mx("example.org."),
+            mx("mail.example.org."),
+        ];
+        let client = Client::new(Config {
+            dns: mock_dns(answers),
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(
+            client.suggest_parent_domain("john@mail.example.com").await,
+            Some("john@example.com".into())
+        );
+        // The subdomain receives mail itself
+        assert_eq!(
+            client.suggest_parent_domain("john@mail.example.org").await,
+            None
+        );
+        // The parent domain doesn't receive mail
+        assert_eq!(
+            client.suggest_parent_domain("john@mail.example.net").await,
+            None
+        );
+        // Not a subdomain
+        assert_eq!(client.suggest_parent_domain("john@example.com").await, None);
+        assert_eq!(client.suggest_parent_domain("john@[127.0.0.1]").await, None);
+    }
+
+    #[tokio::test]
+    async fn search_domains() {
+        let dns = mock_dns(vec![Record::from_rdata(
+            Name::from_ascii("intranet.corp.example.").unwrap(),
+            300,
+            RData::MX(MX::new(10, Name::from_ascii("mx.corp.example.").unwrap())),
+        )]);
+        let config = |search| Config {
+            dns: DnsConfig {
+                search,
+                ..dns.clone()
+            },
+            ..Default::default()
+        };
+        let domain = Domain::new("intranet").unwrap();
+
+        let search = config(SearchDomains::Custom(vec!["corp.example".into()]));
+        let default = config(SearchDomains::default());
+        let invalid = config(SearchDomains::Custom(vec!["corp..example".into()]));
+        assert!(Client::new(invalid).is_err());
+        let search_resolver = dns::resolver(&search.dns).unwrap();
+        let default_resolver = dns::resolver(&default.dns).unwrap();
+
+        let hosts = get_hosts(&domain, &search, &search_resolver).await.unwrap();
+        assert_eq!(hosts[0].host, Name::from_ascii("mx.corp.example.").unwrap());
+        // Without search domains only `intranet.` is queried
+        assert!(
+            get_hosts(&domain, &default, &default_resolver)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn null_mx() {
+        let name = |name| Name::from_ascii(name).unwrap();
+        let config = Config {
+            implicit_mx: ImplicitMx::Enabled {
+                parked_addresses: vec![],
+            },
+            dns: mock_dns(vec![
+                Record::from_rdata(name("example.com."), 300, RData::MX(MX::new(0, name(".")))),
+                Record::from_rdata(name("example.com."), 300, RData::A(A(Ipv4Addr::LOCALHOST))),
+            ]),
+            ..Default::default()
+        };
+        let resolver = dns::resolver(&config.dns).unwrap();
+        let domain = Domain::new("example.com").unwrap();
+
+        // The domain accepts no mail, so its address isn't connected to
+        assert!(matches!(
+            get_hosts(&domain, &config, &resolver).await,
+            Err(Error::NoMxRecords)
+        ));
+    }
+
+    #[tokio::test]
+    async fn implicit_mx_ipv6() {
+        let listener = TcpListener::bind("[::1]:0").await.unwrap();
+        let client = Client::new(Config {
+            port: listener.local_addr().unwrap().port(),
+            implicit_mx: ImplicitMx::Enabled {
+                parked_addresses: vec![],
+            },
+            dns: mock_dns(vec![Record::from_rdata(
+                Name::from_ascii("v6.example.").unwrap(),
+                300,
+                RData::AAAA(Ipv6Addr::LOCALHOST.into()),
+            )]),
+            ..Default::default()
+        })
+        .unwrap();
+        let smtp_server = tokio::spawn(serve_smtp(listener));
+
+        assert_eq!(client.warm_up("v6.example").await, CheckResult::Success);
+        assert_eq!(
+            client.check("john.doe@v6.example").await,
+            CheckResult::Success
+        );
+        smtp_server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn implicit_mx() {
+        let address = Record::from_rdata(
+            Name::from_ascii("parked.example.").unwrap(),
+            300,
+            RData::A(Ipv4Addr::LOCALHOST.into()),
+        );
+        let dns = mock_dns(vec![address]);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let closed = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let unreachable = closed.local_addr().unwrap().port();
+        drop(closed);
+
+        let check = |implicit_mx, port| {
+            let client = Client::new(Config {
+                port,
+                timeout: Some(Duration::from_secs(1)),
+                implicit_mx,
+                dns: dns.clone(),
+                ..Default::default()
+            })
+            .unwrap();
+            async move { client.check("john.doe@parked.example").await }
+        };
+        let enabled = ImplicitMx::Enabled {
+            parked_addresses: vec![],
+        };
+
+        assert_eq!(
+            check(ImplicitMx::Disabled, port).await,
+            CheckResult::Failure(FailureReason::NoMxRecords)
+        );
+        // The only address doesn't answer on the SMTP port
+        assert_eq!(
+            check(enabled.clone(), unreachable).await,
+            CheckResult::Failure(FailureReason::ParkedDomain)
+        );
+        // Known parking addresses aren't connected to
+        let parked = ImplicitMx::Enabled {
+            parked_addresses: vec![Ipv4Addr::LOCALHOST.into()],
+        };
+        assert_eq!(
+            check(parked, port).await,
+            CheckResult::Failure(FailureReason::ParkedDomain)
+        );
+
+        let smtp_server = tokio::spawn(serve_smtp(listener));
+        assert_eq!(check(enabled, port).await, CheckResult::Success);
+        smtp_server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn events() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(16);
+        let client = Client::new(Config {
+            port,
+            events: Some(sender),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let smtp_server = tokio::spawn(serve_smtp(listener));
+        let address = "john@[127.0.0.1]";
+        assert_eq!(client.check(address).await, CheckResult::Success);
+        smtp_server.abort();
+        drop(client);
+
+        let mut events = Vec::new();
+        while let Some((checked, event)) = receiver.recv().await {
+            assert_eq!(checked, address);
+            events.push(event);
+        }
+        assert_eq!(
+            events,
+            [
+                CheckEvent::Resolving,
+                CheckEvent::Connecting {
+                    host: "127.0.0.1".into(),
+                    port,
+                },
+                CheckEvent::Ehlo,
+                CheckEvent::RcptSent,
+                CheckEvent::Completed(CheckResult::Success),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn unverifiable_providers() {
+        // Skipped without looking up the domain, hence without network access
+        for address in [
+            "thomas@outlook.com",
+            "Thomas@HOTMAIL.com.",
+            "thomas@bluewin.ch",
+        ] {
+            assert_eq!(
+                check(address).await,
+                CheckResult::Uncertain(UncertaintyReason::VerificationUnsupported)
+            );
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = Client::new(Config {
+            port: listener.local_addr().unwrap().port(),
+            mx_overrides: HashMap::from([("bluewin.ch".into(), "127.0.0.1".into())]),
+            unverifiable_providers: vec![],
+            ..Default::default()
+        })
+        .unwrap();
+        let smtp_server = tokio::spawn(serve_smtp(listener));
+        assert_eq!(
+            client.check("thomas@bluewin.ch").await,
+            CheckResult::Success
+        );
+        smtp_server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn deferring_providers() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = Config {
+            port: listener.local_addr().unwrap().port(),
+            mx_overrides: HashMap::from([("gmx.net".into(), "127.0.0.1".into())]),
+            deferring_providers: vec!["gmx.net".into()],
+            ..Default::default()
+        };
+        let smtp_server = tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                serve_session(stream).await;
+            }
+        });
+
+        let client = Client::new(config.clone()).unwrap();
+        assert_eq!(
+            client.check("thomas@GMX.net").await,
+            CheckResult::Uncertain(UncertaintyReason::DeferredVerification)
+        );
+
+        // Trust the acceptance anyway
+        let client = Client::new(Config {
+            deferring_providers: vec![],
+            ..config
+        })
+        .unwrap();
+        assert_eq!(client.check("thomas@gmx.net").await, CheckResult::Success);
+        smtp_server.abort();
+    }
+
+    /// Serve an SMTP session which refuses to relay to the recipient
+    async fn deny_relaying(listener: TcpListener) {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        writer.write_all(b"220 mock\r\n").await.unwrap();
+        let mut lines = BufReader::new(reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let response = if line.starts_with("RCPT") {
+                "554 5.7.1 Relay access denied"
+            } else {
+                "250 OK"
+            };
+            writer
+                .write_all(format!("{response}\r\n").as_bytes())
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn relay_denied_unreachable_fallback() {
+        let relay = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let closed = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = Config {
+            port: closed.local_addr().unwrap().port(),
+            ports: HashMap::from([("localhost".into(), relay.local_addr().unwrap().port())]),
+            ..Default::default()
+        };
+        drop(closed);
+        let relay_server = tokio::spawn(deny_relaying(relay));
+
+        let servers = [
+            MailServer {
+                host: Name::from_ascii("localhost").unwrap(),
+                preference: Some(10),
+                implicit: false,
+            },
+            MailServer {
+                host: Name::from_ascii("127.0.0.1").unwrap(),
+                preference: Some(20),
+                implicit: false,
+            },
+        ];
+        let domain = Domain::new("example.com").unwrap();
+        let mut session = Session::default();
+        let result = verify_servers(
+            "john.doe",
+            &domain,
+            &servers,
+            &config,
+            &Pool::default(),
+            &mut session,
+        )
+        .await;
+        relay_server.abort();
+
+        // The unreachable backup tells nothing, so the relay denial is reported
+        assert_eq!(
+            check_result(result, RejectionPrecedence::default(), &[]),
+            CheckResult::Uncertain(UncertaintyReason::RelayDenied)
+        );
+        assert_eq!(session.phase, Some(Phase::Rcpt));
+        assert_eq!(session.mx_preference, Some(10));
+        assert!(!session.used_fallback);
+    }
+
+    #[tokio::test]
+    async fn relay_denied_fallback() {
+        let relay = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = Config {
+            port: listener.local_addr().unwrap().port(),
+            ports: HashMap::from([("localhost".into(), relay.local_addr().unwrap().port())]),
+            ..Default::default()
+        };
+        let relay_server = tokio::spawn(deny_relaying(relay));
+        let server = tokio::spawn(serve_smtp(listener));
+
+        let servers = [
+            MailServer {
+                host: Name::from_ascii("localhost").unwrap(),
+                preference: Some(10),
+                implicit: false,
+            },
+            MailServer {
+                host: Name::from_ascii("127.0.0.1").unwrap(),
+                preference: Some(20),
+                implicit: false,
+            },
+        ];
+        let domain = Domain::new("example.com").unwrap();
+        let mut session = Session::default();
+        let result = verify_servers(
+            "john.doe",
+            &domain,
+            &servers,
+            &config,
+            &Pool::default(),
+            &mut session,
+        )
+        .await;
+        relay_server.abort();
+        server.abort();
+
+        assert!(result.is_ok());
+        assert_eq!(session.mx_preference, Some(20));
+        assert!(session.used_fallback);
+    }
+
+    #[tokio::test]
+    async fn mx_fallback() {
+        let closed = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let unreachable = closed.local_addr().unwrap().port();
+        drop(closed);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = Config {
+            port: listener.local_addr().unwrap().port(),
+            ports: HashMap::from([("localhost".into(), unreachable)]),
+            ..Default::default()
+        };
+        let server = tokio::spawn(serve_smtp(listener));
+
+        let servers = [
+            MailServer {
+                host: Name::from_ascii("localhost").unwrap(),
+                preference: Some(10),
+                implicit: false,
+            },
+            MailServer {
+                host: Name::from_ascii("127.0.0.1").unwrap(),
+                preference: Some(20),
+                implicit: false,
+            },
+        ];
+        let domain = Domain::new("example.com").unwrap();
+        let mut session = Session::default();
+        let result = verify_servers(
+            "john.doe",
+            &domain,
+            &servers,
+            &config,
+            &Pool::default(),
+            &mut session,
+        )
+        .await;
+        server.abort();
+
+        assert!(result.is_ok());
+        assert_eq!(session.mx_preference, Some(20));
+        assert!(session.used_fallback);
+        assert_eq!(session.port, Some(config.port));
+    }
+
+    #[tokio::test]
+    async fn all_mx_unreachable() {
+        let closed = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = Config {
+            port: closed.local_addr().unwrap().port(),
+            ..Default::default()
+        };
+        drop(closed);
+
+        let server = |host, preference| MailServer {
+            host: Name::from_ascii(host).unwrap(),
+            preference: Some(preference),
+            implicit: false,
+        };
+        let servers = [server("localhost", 10), server("127.0.0.1", 20)];
+        let domain = Domain::new("example.com").unwrap();
+        let mut session = Session::default();
+        let result = verify_servers(
+            "john.doe",
+            &domain,
+            &servers,
+            &config,
+            &Pool::default(),
+            &mut session,
+        )
+        .await;
+        assert!(matches!(result, Err(Error::AllMxUnreachable)));
+        assert_eq!(session.phase, Some(Phase::Connect));
+        assert_eq!(
+            check_result(result, RejectionPrecedence::default(), &[]),
+            CheckResult::Uncertain(UncertaintyReason::AllMxUnreachable)
+        );
+
+        // A single mail server being down says nothing more than the error itself
+        let result = verify_servers(
+            "john.doe",
+            &domain,
+            &servers[..1],
+            &config,
+            &Pool::default(),
+            &mut Session::default(),
+        )
+        .await;
+        assert!(matches!(result, Err(Error::Io(_))));
+    }
+
+    #[tokio::test]
+    async fn connection_attempt_budget() {
+        let closed = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let refusing = closed.local_addr().unwrap().port();
+        drop(closed);
+        // Never accepted, so the greeting never arrives
+        let silent = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = Config {
+            port: refusing,
+            fallback_ports: vec![silent.local_addr().unwrap().port()],
+            timeout: Some(Duration::from_millis(100)),
+            max_connection_attempts: Some(3),
+            ..Default::default()
+        };
+
+        let servers = [10, 20, 30].map(|preference| MailServer {
+            host: Name::from_ascii("127.0.0.1").unwrap(),
+            preference: Some(preference),
+            implicit: false,
+        });
+        let domain = Domain::new("example.com").unwrap();
+        let mut session = Session::default();
+        let result = verify_servers(
+            "john.doe",
+            &domain,
+            &servers,
+            &config,
+            &Pool::default(),
+            &mut session,
+        )
+        .await;
+
+        // Both ports of the first server and the first port of the second one.
+        // Its fallback port would have timed out, the third server wasn't tried at all.
+        assert_eq!(session.connection_attempts, 3);
+        assert!(matches!(result, Err(Error::Io(_))));
+        assert_eq!(session.port, None);
+    }
+
+    #[tokio::test]
+    async fn check_rdns() {
+        let name = |name| Name::from_ascii(name).unwrap();
+        let ptr = |ip, target| Record::from_rdata(name(ip), 300, RData::PTR(PTR(name(target))));
+        let a = |host, ip| Record::from_rdata(name(host), 300, RData::A(A(ip)));
+        let answers = vec![
+            ptr("1.2.0.192.in-addr.arpa.", "dynamic.example.net."),
+            ptr("1.2.0.192.in-addr.arpa.", "mail.example.com."),
+            a("dynamic.example.net.", Ipv4Addr::new(198, 51, 100, 1)),
+            a("mail.example.com.", Ipv4Addr::new(192, 0, 2, 1)),
+            // Claims a name it doesn't resolve back from
+            ptr("2.2.0.192.in-addr.arpa.", "dynamic.example.net."),
+        ];
+        let dns = mock_dns(answers);
+        let config = |ip: Ipv4Addr| Config {
+            bind_addr: Some(ip.into()),
+            client_domain: ClientId::Domain("MAIL.example.com".into()),
+            dns: dns.clone(),
+            ..Default::default()
+        };
+        let confirmed = Client::new(config(Ipv4Addr::new(192, 0, 2, 1))).unwrap();
+        let unconfirmed = Client::new(config(Ipv4Addr::new(192, 0, 2, 2))).unwrap();
+        let missing = Client::new(config(Ipv4Addr::new(192, 0, 2, 3))).unwrap();
+
+        let rdns = confirmed.check_rdns().await.unwrap();
+        assert_eq!(rdns.ptr.len(), 2);
+        assert_eq!(rdns.confirmed.as_deref(), Some("mail.example.com."));
+        assert!(rdns.is_forward_confirmed());
+        assert!(rdns.matches_client_domain);
+
+        let rdns = unconfirmed.check_rdns().await.unwrap();
+        assert_eq!(rdns.ptr, vec!["dynamic.example.net.".to_string()]);
+        assert!(!rdns.is_forward_confirmed());
+        assert!(!rdns.matches_client_domain);
+
+        let rdns = missing.check_rdns().await.unwrap();
+        assert!(rdns.ptr.is_empty());
+        assert!(!rdns.is_forward_confirmed());
+    }
+
+    #[tokio::test]
+    async fn ptr_client_domain() {
+        let ptr = Record::from_rdata(
+            Name::from_ascii("1.2.0.192.in-addr.arpa.").unwrap(),
+            300,
+            RData::PTR(PTR(Name::from_ascii("mail.example.com.").unwrap())),
+        );
+        let dns = mock_dns(vec![ptr]);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = Config {
+            port: listener.local_addr().unwrap().port(),
+            bind_addr: Some(Ipv4Addr::new(192, 0, 2, 1).into()),
+            client_domain_source: ClientDomainSource::Ptr,
+            dns,
+            ..Default::default()
+        };
+        let client_domain = Client::new(config.clone())
+            .unwrap()
+            .ptr_client_domain()
+            .await;
+        assert_eq!(
+            client_domain,
+            Some(ClientId::Domain("mail.example.com.".into()))
+        );
+
+        // The bind address isn't ours, so connect from any address
+        let config = Config {
+            bind_addr: None,
+            ..config
+        };
+        let smtp_server = tokio::spawn(serve_smtp(listener));
+        let mut session = Session {
+            client_domain,
+            ..Default::default()
+        };
+        let servers = [MailServer {
+            host: Name::from_ascii("127.0.0.1").unwrap(),
+            preference: None,
+            implicit: false,
+        }];
+        let domain = Domain::new("example.com").unwrap();
+        verify_servers(
+            "john.doe",
+            &domain,
+            &servers,
+            &config,
+            &Pool::default(),
+            &mut session,
+        )
+        .await
+        .unwrap();
+        smtp_server.abort();
+
+        assert!(
+            session
+                .transcript
+                .lines()
+                .iter()
+                .any(|line| line.line == "EHLO mail.example.com.")
+        );
+    }
+
+    #[tokio::test]
+    async fn ptr_client_domain_without_bind_addr() {
+        let client = Client::new(Config {
+            client_domain_source: ClientDomainSource::Ptr,
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(client.ptr_client_domain().await, None);
+    }
+
+    #[tokio::test]
+    async fn mx_count() {
+        let mx = |preference, host| {
+            Record::from_rdata(
+                Name::from_ascii("example.com.").unwrap(),
+                300,
+                RData::MX(MX::new(preference, Name::from_ascii(host).unwrap())),
+            )
+        };
+        let answers = vec![
+            mx(10, "mx1.example.com."),
+            mx(20, "mx2.example.com."),
+            mx(30, "mx3.example.com."),
+        ];
+        // The mail servers don't exist, only the lookup is of interest
+        let result = Client::new(Config {
+            timeout: Some(Duration::from_millis(100)),
+            dns: mock_dns(answers),
+            ..Default::default()
+        })
+        .unwrap()
+        .check_detailed("john.doe@example.com")
+        .await;
+
+        assert_eq!(result.mx_count, 3);
+        assert_eq!(result.mx_preference, None);
+    }
+
+    #[tokio::test]
+    async fn timeout() {
+        let result = Client::new(Config {
+            timeout: Some(Duration::ZERO),
+            ..Default::default()
+        })
+        .unwrap()
+        .check("a@gmail.com")
+        .await;
+
+        assert_eq!(result, CheckResult::Uncertain(UncertaintyReason::Timeout));
+    }
+
+    #[tokio::test]
     async fn domain_literal_syntax() {
         // $ dig tuta.com MX +short
         // 0 mail.tutanota.de.
@@ -494,10 +3796,20 @@ mod tests {
         );
     }
 
+    /// Check without skipping the [`Config::unverifiable_providers`]
+    async fn check_unverifiable(address: &str) -> CheckResult {
+        let client = Client::new(Config {
+            unverifiable_providers: vec![],
+            ..Default::default()
+        })
+        .unwrap();
+        client.check(address).await
+    }
+
     #[tokio::test]
     async fn blocklisting() {
         assert_eq!(
-            check("thomas@bluewin.ch").await,
+            check_unverifiable("thomas@bluewin.ch").await,
             CheckResult::Uncertain(UncertaintyReason::Blocklisted)
         );
     }
@@ -505,12 +3817,12 @@ mod tests {
     #[tokio::test]
     async fn no_reverse_hostname() {
         assert_eq!(
-            check("thomas@outlook.com").await,
+            check_unverifiable("thomas@outlook.com").await,
             CheckResult::Uncertain(UncertaintyReason::NoReverseHostname)
         );
 
         assert_eq!(
-            check("thomas@hotmail.com").await,
+            check_unverifiable("thomas@hotmail.com").await,
             CheckResult::Uncertain(UncertaintyReason::NoReverseHostname)
         );
     }
@@ -527,14 +3839,15 @@ mod tests {
 
         // In an ideal world where SMTP servers aren't lying we would instead see:
         // CheckResult::Failure(FailureReason::NoSuchAddress)
+        // These providers are therefore part of `DEFAULT_CATCH_ALL_PROVIDERS`.
 
         assert_eq!(
             check("a309f2f034590l290@yahoo.com").await,
-            CheckResult::Success
+            CheckResult::Uncertain(UncertaintyReason::CatchAll)
         );
         assert_eq!(
             check("a309f2f034590l290@aol.com").await,
-            CheckResult::Success
+            CheckResult::Uncertain(UncertaintyReason::CatchAll)
         );
     }
 }
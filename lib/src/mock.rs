@@ -0,0 +1,80 @@
+use std::{net::Ipv4Addr, time::Duration};
+
+use hickory_resolver::proto::{
+    op::{Message, MessageType},
+    rr::Record,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream, UdpSocket},
+    time::Instant,
+};
+
+use crate::{DnsConfig, DnsUpstream};
+
+/// Configuration of a DNS server answering every query with all of the records,
+/// of which the resolver picks those matching the query.
+/// The server runs until the runtime of the test shuts down.
+pub(crate) fn mock_dns(answers: Vec<Record>) -> DnsConfig {
+    let socket = std::net::UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    socket.set_nonblocking(true).unwrap();
+    let socket = UdpSocket::from_std(socket).unwrap();
+    let port = socket.local_addr().unwrap().port();
+    tokio::spawn(serve_dns(socket, answers));
+
+    DnsConfig {
+        upstream: Some(DnsUpstream {
+            addresses: vec![Ipv4Addr::LOCALHOST.into()],
+            port: Some(port),
+            tls_name: None,
+        }),
+        ..Default::default()
+    }
+}
+
+async fn serve_dns(socket: UdpSocket, answers: Vec<Record>) {
+    let mut buffer = [0; 4096];
+    loop {
+        let (length, peer) = socket.recv_from(&mut buffer).await.unwrap();
+        let query = Message::from_vec(&buffer[..length]).unwrap();
+        let mut response = Message::new();
+        response
+            .set_id(query.id())
+            .set_message_type(MessageType::Response)
+            .set_op_code(query.op_code())
+            .set_recursion_desired(query.recursion_desired())
+            .set_recursion_available(true)
+            .add_queries(query.queries().to_vec())
+            .add_answers(answers.clone());
+        socket
+            .send_to(&response.to_vec().unwrap(), peer)
+            .await
+            .unwrap();
+    }
+}
+
+/// Accept a single SMTP session, see [`serve_session`]
+pub(crate) async fn serve_smtp(listener: TcpListener) -> Vec<(String, Duration)> {
+    let (stream, _) = listener.accept().await.unwrap();
+    serve_session(stream).await
+}
+
+/// Serve an SMTP session accepting every command and return the commands received,
+/// with the time since the session started
+pub(crate) async fn serve_session(stream: TcpStream) -> Vec<(String, Duration)> {
+    let start = Instant::now();
+    let (reader, mut writer) = stream.into_split();
+    writer.write_all(b"220 mock\r\n").await.unwrap();
+
+    let mut commands = Vec::new();
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let response = if line == "QUIT" { "221 Bye" } else { "250 OK" };
+        commands.push((line, start.elapsed()));
+        writer
+            .write_all(format!("{response}\r\n").as_bytes())
+            .await
+            .unwrap();
+    }
+    commands
+}
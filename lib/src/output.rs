@@ -0,0 +1,183 @@
+use tokio::{
+    io::{AsyncWrite, AsyncWriteExt, BufWriter},
+    sync::mpsc::Receiver,
+};
+
+use crate::{CheckResult, UncertaintyReason};
+
+/// Format of the records written by [`write_results`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Comma-separated values with the header `address,result,reason,smtp_code`.
+    /// `result` is `success`, `uncertain` or `failure` and `reason` its code, e.g.
+    /// [`UncertaintyReason::code`]. `reason` and `smtp_code` are empty if they don't apply.
+    /// Fields are quoted as per [RFC4180](https://www.rfc-editor.org/rfc/rfc4180) where needed.
+    Csv,
+    /// One JSON object per line of the form described in
+    /// [`Client::check_json`](crate::Client::check_json)
+    #[cfg(feature = "serde")]
+    JsonLines,
+}
+
+/// Write the results to `writer` as they are received, e.g. from
+/// [`Client::check_channel`](crate::Client::check_channel), one record per line
+/// in the order of completion.
+/// Records are buffered and flushed whenever no further result is ready,
+/// so the output keeps up with the checks without writing each record on its own.
+///
+/// Returns the number of records written once the results are exhausted.
+///
+/// # Errors
+///
+/// Returns the first error writing to `writer`. The results are dropped then,
+/// which stops checking the remaining addresses.
+pub async fn write_results<W>(
+    mut results: Receiver<(String, CheckResult)>,
+    writer: W,
+    format: OutputFormat,
+) -> std::io::Result<usize>
+where
+    W: AsyncWrite + Unpin,
+{
+    let mut writer = BufWriter::new(writer);
+    if format == OutputFormat::Csv {
+        writer
+            .write_all(b"address,result,reason,smtp_code\n")
+            .await?;
+    }
+
+    let mut written = 0;
+    while let Some((address, result)) = results.recv().await {
+        let record = match format {
+            OutputFormat::Csv => csv_record(&address, &result),
+            #[cfg(feature = "serde")]
+            OutputFormat::JsonLines => format!("{}\n", crate::json::to_json(&address, &result)),
+        };
+        writer.write_all(record.as_bytes()).await?;
+        written += 1;
+
+        if results.is_empty() {
+            writer.flush().await?;
+        }
+    }
+
+    writer.flush().await?;
+    Ok(written)
+}
+
+/// Kind of the result, its reason code and the code of the SMTP response, if any
+pub(crate) fn fields(result: &CheckResult) -> (&'static str, Option<&'static str>, Option<u16>) {
+    match result {
+        CheckResult::Success => ("success", None, None),
+        CheckResult::Uncertain(reason) => {
+            let smtp_code = match reason {
+                UncertaintyReason::NegativeSmtpResponse(response) => {
+                    response.code.to_string().parse::<u16>().ok()
+                }
+                _ => None,
+            };
+            ("uncertain", Some(reason.code()), smtp_code)
+        }
+        CheckResult::Failure(reason) => ("failure", Some(reason.code()), None),
+    }
+}
+
+fn csv_record(address: &str, result: &CheckResult) -> String {
+    let (result, reason, smtp_code) = fields(result);
+    let address = if address.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", address.replace('"', "\"\""))
+    } else {
+        address.to_string()
+    };
+    let smtp_code = smtp_code.map(|code| code.to_string()).unwrap_or_default();
+    format!(
+        "{address},{result},{},{smtp_code}\n",
+        reason.unwrap_or_default()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use async_smtp::response::Response;
+    use tokio::{io, sync::mpsc};
+
+    use super::{OutputFormat, csv_record, write_results};
+    use crate::{CheckResult, Client, Config, UncertaintyReason};
+
+    #[tokio::test]
+    async fn csv() {
+        let client = Client::new(Config {
+            concurrency: 1,
+            ..Default::default()
+        })
+        .unwrap();
+        let results = client.check_reader(Cursor::new(b"a\n\"x,y\"@\n".to_vec()));
+
+        let mut output = Vec::new();
+        let written = write_results(results, &mut output, OutputFormat::Csv)
+            .await
+            .unwrap();
+        assert_eq!(written, 2);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "address,result,reason,smtp_code\n\
+             a,failure,invalid_address_format,\n\
+             \"\"\"x,y\"\"@\",failure,invalid_address_format,\n"
+        );
+
+        let response = "554 Go away\r\n".parse::<Response>().unwrap();
+        let result = CheckResult::Uncertain(UncertaintyReason::NegativeSmtpResponse(response));
+        assert_eq!(
+            csv_record("john.doe@example.com", &result),
+            "john.doe@example.com,uncertain,negative_smtp_response,554\n"
+        );
+        assert_eq!(
+            csv_record("john.doe@example.com", &CheckResult::Success),
+            "john.doe@example.com,success,,\n"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[tokio::test]
+    async fn json_lines() {
+        let (sender, results) = mpsc::channel(2);
+        sender
+            .send(("john.doe@example.com".into(), CheckResult::Success))
+            .await
+            .unwrap();
+        drop(sender);
+
+        let mut output = Vec::new();
+        write_results(results, &mut output, OutputFormat::JsonLines)
+            .await
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "{\"address\":\"john.doe@example.com\",\"reason\":null,\"result\":\"success\",\"smtp_code\":null}\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn write_error() {
+        let (sender, results) = mpsc::channel(1);
+        let (writer, reader) = io::duplex(64);
+        drop(reader);
+
+        let feeder = tokio::spawn(async move {
+            while sender
+                .send(("a".into(), CheckResult::Success))
+                .await
+                .is_ok()
+            {}
+        });
+        assert!(
+            write_results(results, writer, OutputFormat::Csv)
+                .await
+                .is_err()
+        );
+        // The results were dropped, so the sender stops
+        feeder.await.unwrap();
+    }
+}
@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use async_smtp::EmailAddress;
+
+use crate::{ClientBuildError, Config, ResolutionProfile, StrategyChain};
+
+/// Changes to the [`Config`] of a [`Client`](crate::Client) for a single check,
+/// see [`Client::check_with`](crate::Client::check_with).
+///
+/// Fields set to `None` keep the value of the client's configuration.
+/// Settings tied to the client's state, e.g. [`Config::dns`] or [`Config::concurrency`],
+/// can't be overridden.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOverrides {
+    /// Overrides [`Config::timeout`]
+    pub timeout: Option<Duration>,
+    /// Overrides [`Config::sender_addresses`]
+    pub sender_addresses: Option<Vec<EmailAddress>>,
+    /// Overrides [`Config::strategies`], e.g. to skip the catch-all probe
+    pub strategies: Option<StrategyChain>,
+    /// Overrides [`Config::demote_catch_all`]
+    pub demote_catch_all: Option<bool>,
+    /// Overrides [`Config::double_check`]
+    pub double_check: Option<Duration>,
+    /// Overrides [`Config::resolution`]
+    pub resolution: Option<ResolutionProfile>,
+    /// Overrides [`Config::strip_subaddress`]
+    pub strip_subaddress: Option<bool>,
+}
+
+impl ConfigOverrides {
+    /// Use a single sender address, see [`Config::with_sender_address`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address string contains invalid characters
+    pub fn with_sender_address(
+        mut self,
+        address: String,
+    ) -> std::result::Result<Self, ClientBuildError> {
+        let address =
+            EmailAddress::new(address).map_err(|_| ClientBuildError::InvalidEmailAddress)?;
+        self.sender_addresses = Some(vec![address]);
+        Ok(self)
+    }
+
+    /// The configuration with these overrides applied
+    pub(crate) fn apply(self, config: &Config) -> Config {
+        let config = config.clone();
+        Config {
+            timeout: self.timeout.or(config.timeout),
+            sender_addresses: self.sender_addresses.unwrap_or(config.sender_addresses),
+            strategies: self.strategies.unwrap_or(config.strategies),
+            demote_catch_all: self.demote_catch_all.unwrap_or(config.demote_catch_all),
+            double_check: self.double_check.or(config.double_check),
+            resolution: self.resolution.unwrap_or(config.resolution),
+            strip_subaddress: self.strip_subaddress.unwrap_or(config.strip_subaddress),
+            ..config
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use crate::{Client, Domain, ParsedAddress, dns, lookup_mx, parse_address};
+
+impl Client {
+    /// Suggest the address at the parent domain for an address at a subdomain without
+    /// mail servers, e.g. `john@example.com` for `john@mail.example.com`.
+    /// Such addresses are more often typos than subdomains receiving mail, so this can offer
+    /// a correction after a check resulted in
+    /// [`FailureReason::NoMxRecords`](crate::FailureReason::NoMxRecords).
+    /// The suggested address itself isn't checked, only DNS is queried.
+    ///
+    /// Returns `None` if the address is invalid, its domain isn't a subdomain
+    /// or has MX records, or the parent domain has none.
+    pub async fn suggest_parent_domain(&self, mail: &str) -> Option<String> {
+        let ParsedAddress {
+            local_part, domain, ..
+        } = parse_address(mail).ok()?;
+        let parent = parent(&domain)?;
+
+        if self.has_mx(&domain).await || !self.has_mx(&parent).await {
+            return None;
+        }
+        Some(format!("{local_part}@{}", parent.original()))
+    }
+
+    async fn has_mx(&self, domain: &Domain) -> bool {
+        let name = dns::query_name(domain, &self.config.dns);
+        lookup_mx(&name, &self.resolver, self.config.dns.dnssec)
+            .await
+            .is_ok_and(|records| !records.is_empty())
+    }
+}
+
+/// The domain without its first label, unless that leaves a top-level domain
+fn parent(domain: &Domain) -> Option<Domain> {
+    if domain.is_literal() {
+        return None;
+    }
+    let (_, parent) = domain.original().split_once('.')?;
+    if !parent.trim_end_matches('.').contains('.') {
+        return None;
+    }
+    Domain::new(parent)
+}
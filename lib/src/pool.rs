@@ -0,0 +1,327 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use async_smtp::commands::NoopCommand;
+use hickory_resolver::Name;
+use tokio::{
+    io::{AsyncWriteExt, BufStream},
+    net::TcpStream,
+    time::{self, Instant},
+};
+
+use crate::{
+    CheckResult, Client, Domain, Error, MailServer, Result, Stream, get_hosts, host_name,
+    parse_address,
+    smtp::Connection,
+    tcp_connect,
+    transcript::{Recorder, Transcript},
+};
+
+/// Mail server hosts and connections prepared by [`Client::warm_up`].
+/// Entries are used at most once and expire after [`Config::warm_up_lifetime`](crate::Config::warm_up_lifetime).
+#[derive(Debug, Default)]
+pub(crate) struct Pool {
+    hosts: Mutex<HashMap<Domain, (Vec<MailServer>, Instant)>>,
+    connections: Mutex<HashMap<(Name, u16), (Parked, Instant)>>,
+}
+
+/// Connection parked by [`Client::warm_up`]
+#[derive(Debug)]
+enum Parked {
+    /// The greeting is still waiting to be read
+    Fresh(TcpStream),
+    /// Greeted and kept alive by a task, see [`Config::keepalive_interval`](crate::Config::keepalive_interval).
+    /// Empty once taken or closed by the task.
+    KeptAlive(Arc<tokio::sync::Mutex<Option<Greeted>>>),
+}
+
+/// Connection taken from the [`Pool`]
+pub(crate) enum Reused {
+    /// The greeting is still waiting to be read
+    Fresh(TcpStream),
+    /// The greeting was already read
+    Greeted(Greeted),
+}
+
+/// Connection whose greeting was read
+pub(crate) struct Greeted {
+    /// Connection recording the greeting and the keepalive commands into its own transcript
+    pub(crate) connection: Connection<Stream>,
+    pub(crate) peer_addr: Option<SocketAddr>,
+}
+
+impl std::fmt::Debug for Greeted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Greeted")
+            .field("peer_addr", &self.peer_addr)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Pool {
+    fn park_hosts(&self, domain: Domain, hosts: Vec<MailServer>) {
+        lock(&self.hosts).insert(domain, (hosts, Instant::now()));
+    }
+
+    fn park_connection(&self, host: Name, port: u16, connection: Parked) {
+        lock(&self.connections).insert((host, port), (connection, Instant::now()));
+    }
+
+    /// Take the mail servers of the domain, if they were resolved within `lifetime`
+    pub(crate) fn take_hosts(
+        &self,
+        domain: &Domain,
+        lifetime: Duration,
+    ) -> Option<Vec<MailServer>> {
+        let (hosts, parked) = lock(&self.hosts).remove(domain)?;
+        (parked.elapsed() < lifetime).then_some(hosts)
+    }
+
+    /// Take a connection to the host, if it was opened within `lifetime` and is still open
+    pub(crate) async fn take_connection(
+        &self,
+        host: &Name,
+        port: u16,
+        lifetime: Duration,
+    ) -> Option<Reused> {
+        let (connection, parked) = lock(&self.connections).remove(&(host.clone(), port))?;
+        if parked.elapsed() >= lifetime {
+            return None;
+        }
+        match connection {
+            Parked::Fresh(stream) => Some(Reused::Fresh(stream)),
+            Parked::KeptAlive(slot) => slot.lock().await.take().map(Reused::Greeted),
+        }
+    }
+
+    /// Drop expired hosts and close expired connections
+    fn prune(&self, lifetime: Duration) {
+        lock(&self.hosts).retain(|_, (_, parked)| parked.elapsed() < lifetime);
+        lock(&self.connections).retain(|_, (_, parked)| parked.elapsed() < lifetime);
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl Client {
+    /// Prepare checking addresses of the given domain (e.g. `example.com`) to reduce the
+    /// latency of the next [`Client::check`], for example while a user is still typing.
+    ///
+    /// The mail servers of the domain are resolved and, if
+    /// [`Config::park_connections`](crate::Config::park_connections) is enabled,
+    /// a connection to the preferred one is opened and parked.
+    /// The next check of an address of the domain uses the resolved hosts and takes over the
+    /// parked connection instead of opening a new one. Both are used at most once.
+    ///
+    /// Unused hosts and connections expire after
+    /// [`Config::warm_up_lifetime`](crate::Config::warm_up_lifetime).
+    /// Expired connections are closed on the next call to this method
+    /// and all of them are closed once the last clone of the [`Client`] is dropped,
+    /// except for connections kept alive with
+    /// [`Config::keepalive_interval`](crate::Config::keepalive_interval),
+    /// which are closed with `QUIT` once expired.
+    ///
+    /// Returns [`CheckResult::Success`] if the domain could be prepared, otherwise the result
+    /// a check of an address of the domain would most likely result in.
+    pub async fn warm_up(&self, domain: &str) -> CheckResult {
+        self.warm_up_inner(domain).await.into()
+    }
+
+    async fn warm_up_inner(&self, domain: &str) -> Result {
+        let lifetime = self.config.warm_up_lifetime;
+        self.pool.prune(lifetime);
+
+        let domain = parse_address(&format!("postmaster@{domain}"))
+            .map_err(|_| Error::InvalidAddressFormat)?
+            .domain;
+
+        let hosts = get_hosts(&domain, &self.config, &self.resolver).await?;
+        let host = hosts[0].host.clone();
+        self.pool.park_hosts(domain, hosts);
+
+        if self.config.park_connections {
+            let port = self.config.port_for(&host_name(&host));
+            let connect = self.open(&host, port);
+            let connection = match self.config.timeout {
+                Some(timeout) => time::timeout(timeout, connect)
+                    .await
+                    .map_err(|_| Error::Timeout)??,
+                None => connect.await?,
+            };
+            self.pool.park_connection(host, port, connection);
+        }
+
+        Ok(())
+    }
+
+    /// Open a connection to park. With a [`Config::keepalive_interval`](crate::Config::keepalive_interval)
+    /// the greeting is read and a task keeps the connection alive.
+    async fn open(&self, host: &Name, port: u16) -> Result<Parked> {
+        let stream = tcp_connect(host, port, self.config.bind_addr).await?;
+        let Some(interval) = self.config.keepalive_interval else {
+            return Ok(Parked::Fresh(stream));
+        };
+
+        let peer_addr = stream.peer_addr().ok();
+        let stream = BufStream::new(Recorder::new(stream, Transcript::default()));
+        let mut connection = Connection::new(stream, self.config.response_limits);
+        connection.read_greeting().await?;
+
+        let slot = Arc::new(tokio::sync::Mutex::new(Some(Greeted {
+            connection,
+            peer_addr,
+        })));
+        let deadline = Instant::now() + self.config.warm_up_lifetime;
+        tokio::spawn(keep_alive(slot.clone(), interval, deadline));
+        Ok(Parked::KeptAlive(slot))
+    }
+}
+
+/// Send `NOOP` every `interval` until the connection is taken,
+/// and close it with `QUIT` at the `deadline`. Connections the server closed are dropped.
+async fn keep_alive(
+    slot: Arc<tokio::sync::Mutex<Option<Greeted>>>,
+    interval: Duration,
+    deadline: Instant,
+) {
+    loop {
+        time::sleep_until(deadline.min(Instant::now() + interval)).await;
+        let mut slot = slot.lock().await;
+        let Some(greeted) = slot.as_mut() else {
+            return;
+        };
+
+        if Instant::now() >= deadline {
+            // Once the server acknowledged, close our side as well.
+            // Otherwise the server already closed the connection or misbehaves.
+            if greeted.connection.quit().await.is_ok() {
+                let _ = greeted.connection.stream_mut().shutdown().await;
+            }
+            *slot = None;
+            return;
+        }
+        if greeted.connection.command(NoopCommand).await.is_err() {
+            *slot = None;
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, str::FromStr, time::Duration};
+
+    use hickory_resolver::Name;
+    use tokio::{
+        io::BufStream,
+        net::{TcpListener, TcpStream},
+    };
+
+    use super::Pool;
+    use crate::{
+        CheckResult, Client, Config, Direction, Domain, MailServer, ResponseLimits, TranscriptLine,
+        mock::serve_smtp, smtp::Connection,
+    };
+
+    fn keepalive_client(listener: &TcpListener) -> Client {
+        Client::new(Config {
+            port: listener.local_addr().unwrap().port(),
+            mx_overrides: HashMap::from([("example.com".into(), "127.0.0.1".into())]),
+            park_connections: true,
+            warm_up_lifetime: Duration::from_secs(35),
+            keepalive_interval: Some(Duration::from_secs(10)),
+            timeout: None,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn keepalive() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = keepalive_client(&listener);
+        let server = tokio::spawn(serve_smtp(listener));
+
+        assert_eq!(client.warm_up("example.com").await, CheckResult::Success);
+        let secs = Duration::from_secs;
+        assert_eq!(
+            server.await.unwrap(),
+            [
+                ("NOOP".into(), secs(10)),
+                ("NOOP".into(), secs(20)),
+                ("NOOP".into(), secs(30)),
+                ("QUIT".into(), secs(35)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn quit() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let server = tokio::spawn(serve_smtp(listener));
+
+        let mut connection = Connection::new(BufStream::new(stream), ResponseLimits::default());
+        connection.read_greeting().await.unwrap();
+        assert!(connection.quit().await.unwrap().has_code(221));
+        drop(connection);
+        assert_eq!(server.await.unwrap().len(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn kept_alive_connection_is_used() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = keepalive_client(&listener);
+        let server = tokio::spawn(serve_smtp(listener));
+
+        assert_eq!(client.warm_up("example.com").await, CheckResult::Success);
+        tokio::time::sleep(Duration::from_secs(15)).await;
+        let result = client.check_detailed("john@example.com").await;
+        assert_eq!(result.result, CheckResult::Success);
+
+        // The conversation while parked is part of the transcript
+        let line = |direction, line: &str| TranscriptLine {
+            direction,
+            line: line.into(),
+        };
+        assert_eq!(
+            result.transcript[..4],
+            [
+                line(Direction::Received, "220 mock"),
+                line(Direction::Sent, "NOOP"),
+                line(Direction::Received, "250 OK"),
+                line(Direction::Sent, "EHLO example.com."),
+            ]
+        );
+        drop(client);
+        server.abort();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn hosts_expire() {
+        let pool = Pool::default();
+        let domain = Domain::new("example.com").unwrap();
+        let hosts = vec![MailServer {
+            host: Name::from_str("mx.example.com.").unwrap(),
+            preference: Some(10),
+            implicit: false,
+        }];
+        let lifetime = Duration::from_secs(30);
+
+        pool.park_hosts(domain.clone(), hosts.clone());
+        assert_eq!(pool.take_hosts(&domain, lifetime), Some(hosts.clone()));
+        assert_eq!(pool.take_hosts(&domain, lifetime), None);
+
+        pool.park_hosts(domain.clone(), hosts);
+        tokio::time::advance(lifetime).await;
+        assert_eq!(pool.take_hosts(&domain, lifetime), None);
+    }
+}
@@ -0,0 +1,39 @@
+use crate::{CheckResult, Client, FailureReason, UncertaintyReason};
+
+/// Whether the mail server of a domain accepts its postmaster mailbox,
+/// see [`Client::verify_postmaster`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PostmasterStatus {
+    /// The postmaster mailbox was accepted, so rejections of other addresses
+    /// of the domain are plausible
+    Accepted,
+    /// The postmaster mailbox was rejected, although every domain receiving mail must accept it.
+    /// The mail server likely rejects any address or blocks us, so
+    /// [`CheckResult::Failure`]s of other addresses of the domain are unreliable.
+    Rejected,
+    /// The postmaster mailbox couldn't be verified for the reason given,
+    /// e.g. because the mail server didn't answer
+    Unknown(CheckResult),
+}
+
+impl Client {
+    /// Check the postmaster mailbox of the domain to calibrate the confidence in the results
+    /// of its other addresses. [RFC5321 section 4.5.1](https://www.rfc-editor.org/rfc/rfc5321#section-4.5.1)
+    /// requires every domain receiving mail to accept `postmaster@`.
+    ///
+    /// If the result is [`PostmasterStatus::Rejected`], callers might treat
+    /// [`FailureReason::NoSuchAddress`] of other addresses of the domain as uncertain,
+    /// e.g. by not removing them from a mailing list.
+    /// Accepting the postmaster says nothing about other addresses, as a catch-all
+    /// domain accepts it as well.
+    pub async fn verify_postmaster(&self, domain: &str) -> PostmasterStatus {
+        match self.check(&format!("postmaster@{domain}")).await {
+            CheckResult::Success
+            | CheckResult::Uncertain(
+                UncertaintyReason::CatchAll | UncertaintyReason::DeferredVerification,
+            ) => PostmasterStatus::Accepted,
+            CheckResult::Failure(FailureReason::NoSuchAddress) => PostmasterStatus::Rejected,
+            result => PostmasterStatus::Unknown(result),
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use crate::{CheckResult, FailureReason, UncertaintyReason, heuristics};
+
+/// How uncertain results are resolved, see [`Config::resolution`](crate::Config::resolution)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResolutionProfile {
+    /// Report results as classified
+    #[default]
+    Balanced,
+    /// Prioritize clean lists over keeping every deliverable address:
+    /// results which make delivery unlikely or unverifiable are reported as
+    /// [`FailureReason::Risky`] with the original reason.
+    ///
+    /// - [`UncertaintyReason::CatchAll`]: the address can't be told apart from inexistent ones
+    /// - [`UncertaintyReason::NegativeSmtpResponse`] with a full mailbox
+    ///   (e.g. "552 5.2.2 user is over quota"): the mailbox is most likely abandoned
+    /// - [`UncertaintyReason::RecipientTimeout`]: the server stalls every verification
+    ///   of the address, so retrying won't help
+    ///
+    /// Other reasons stay uncertain, in particular [`UncertaintyReason::Timeout`],
+    /// which usually means that our own network blocks outgoing SMTP traffic.
+    Aggressive,
+}
+
+impl ResolutionProfile {
+    pub(crate) fn resolve(self, result: CheckResult) -> CheckResult {
+        match (self, result) {
+            (ResolutionProfile::Aggressive, CheckResult::Uncertain(reason)) if risky(&reason) => {
+                CheckResult::Failure(FailureReason::Risky(reason))
+            }
+            (_, result) => result,
+        }
+    }
+}
+
+fn risky(reason: &UncertaintyReason) -> bool {
+    match reason {
+        UncertaintyReason::CatchAll | UncertaintyReason::RecipientTimeout => true,
+        UncertaintyReason::NegativeSmtpResponse(response) => heuristics::mailbox_full(response),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_smtp::response::Response;
+
+    use super::ResolutionProfile;
+    use crate::{CheckResult, FailureReason, UncertaintyReason};
+
+    fn aggressive(reason: UncertaintyReason) -> CheckResult {
+        ResolutionProfile::Aggressive.resolve(CheckResult::Uncertain(reason))
+    }
+
+    fn negative(response: &str) -> UncertaintyReason {
+        UncertaintyReason::NegativeSmtpResponse(response.parse::<Response>().unwrap())
+    }
+
+    #[test]
+    fn reclassified() {
+        let over_quota = "552 5.2.2 <thomas@icloud.com>: user is over quota\r\n";
+
+        assert_eq!(
+            aggressive(UncertaintyReason::CatchAll),
+            CheckResult::Failure(FailureReason::Risky(UncertaintyReason::CatchAll))
+        );
+        assert_eq!(
+            aggressive(UncertaintyReason::RecipientTimeout),
+            CheckResult::Failure(FailureReason::Risky(UncertaintyReason::RecipientTimeout))
+        );
+        assert_eq!(
+            aggressive(negative(over_quota)),
+            CheckResult::Failure(FailureReason::Risky(negative(over_quota)))
+        );
+        assert_eq!(
+            aggressive(negative("452 4.2.2 Mailbox full\r\n")),
+            CheckResult::Failure(FailureReason::Risky(negative("452 4.2.2 Mailbox full\r\n")))
+        );
+    }
+
+    #[test]
+    fn unaffected() {
+        assert_eq!(
+            aggressive(UncertaintyReason::Timeout),
+            CheckResult::Uncertain(UncertaintyReason::Timeout)
+        );
+        assert_eq!(
+            aggressive(UncertaintyReason::Blocklisted),
+            CheckResult::Uncertain(UncertaintyReason::Blocklisted)
+        );
+        assert_eq!(
+            aggressive(negative("554 Go away\r\n")),
+            CheckResult::Uncertain(negative("554 Go away\r\n"))
+        );
+        assert_eq!(
+            ResolutionProfile::Aggressive.resolve(CheckResult::Success),
+            CheckResult::Success
+        );
+        assert_eq!(
+            ResolutionProfile::Balanced
+                .resolve(CheckResult::Uncertain(UncertaintyReason::CatchAll)),
+            CheckResult::Uncertain(UncertaintyReason::CatchAll)
+        );
+    }
+}
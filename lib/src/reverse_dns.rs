@@ -0,0 +1,123 @@
+use std::net::{IpAddr, SocketAddr};
+
+use async_smtp::extension::ClientId;
+use hickory_resolver::{Name, TokioResolver};
+use tokio::net::UdpSocket;
+
+use crate::{CheckResult, Client, DnsUpstream, FailureReason, UncertaintyReason};
+
+/// Reverse DNS of the IP address we connect to mail servers from,
+/// see [`Client::check_rdns`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReverseDns {
+    /// IP address we connect from
+    pub ip: IpAddr,
+    /// Names of the PTR records of the address
+    pub ptr: Vec<String>,
+    /// Name of the PTR records which resolves back to the address (forward-confirmed
+    /// reverse DNS), as many mail servers require. `None` if there is none.
+    pub confirmed: Option<String>,
+    /// Whether the confirmed name is the name we introduce ourselves with in EHLO,
+    /// see [`Config::client_domain`](crate::Config::client_domain)
+    pub matches_client_domain: bool,
+}
+
+impl ReverseDns {
+    /// Whether the address has a PTR record resolving back to it
+    #[must_use]
+    pub const fn is_forward_confirmed(&self) -> bool {
+        self.confirmed.is_some()
+    }
+}
+
+impl Client {
+    /// Look up the reverse DNS of the IP address we connect from and whether it matches
+    /// the name we introduce ourselves with, e.g. to explain results of
+    /// [`UncertaintyReason::Blocklisted`] and [`UncertaintyReason::NoReverseHostname`].
+    /// Only DNS is queried.
+    ///
+    /// The address is the [`Config::bind_addr`](crate::Config::bind_addr) or else the local
+    /// address of the route to the internet. Behind NAT that is a private address,
+    /// which never has a meaningful PTR record.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FailureReason::IoError`] if there is no route to determine the address from
+    /// and [`UncertaintyReason::DnsResolverError`] if a lookup fails
+    pub async fn check_rdns(&self) -> Result<ReverseDns, CheckResult> {
+        let ip = match self.config.bind_addr {
+            Some(ip) => ip,
+            None => local_ip()
+                .await
+                .map_err(|e| CheckResult::Failure(FailureReason::IoError(e.to_string())))?,
+        };
+        let ptr = ptr_names(ip, &self.resolver).await?;
+
+        let mut confirmed = None;
+        for name in &ptr {
+            if resolves_to(name, ip, &self.resolver).await? {
+                confirmed = Some(name.clone());
+                break;
+            }
+        }
+
+        let client_domain = match self.ptr_client_domain().await {
+            Some(ClientId::Domain(domain)) => Some(domain),
+            _ => match &self.config.client_domain {
+                ClientId::Domain(domain) => Some(domain.clone()),
+                _ => None,
+            },
+        };
+        let matches_client_domain = confirmed
+            .as_deref()
+            .zip(client_domain.as_deref())
+            .is_some_and(|(confirmed, client_domain)| {
+                confirmed
+                    .trim_end_matches('.')
+                    .eq_ignore_ascii_case(client_domain.trim_end_matches('.'))
+            });
+
+        Ok(ReverseDns {
+            ip,
+            ptr,
+            confirmed,
+            matches_client_domain,
+        })
+    }
+}
+
+/// Local address of the route to the internet. Connecting a UDP socket sends no packets.
+async fn local_ip() -> std::io::Result<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    let remote = DnsUpstream::cloudflare().addresses[0];
+    socket.connect(SocketAddr::new(remote, 53)).await?;
+    Ok(socket.local_addr()?.ip())
+}
+
+async fn ptr_names(ip: IpAddr, resolver: &TokioResolver) -> Result<Vec<String>, CheckResult> {
+    match resolver.reverse_lookup(ip).await {
+        Ok(lookup) => Ok(lookup.iter().map(ToString::to_string).collect()),
+        Err(e) if e.is_no_records_found() => Ok(vec![]),
+        Err(e) => Err(dns_error(&e)),
+    }
+}
+
+/// Whether the name has an address record of the IP address
+async fn resolves_to(
+    name: &str,
+    ip: IpAddr,
+    resolver: &TokioResolver,
+) -> Result<bool, CheckResult> {
+    let Ok(name) = Name::from_ascii(name) else {
+        return Ok(false);
+    };
+    match resolver.lookup_ip(name).await {
+        Ok(lookup) => Ok(lookup.iter().any(|address| address == ip)),
+        Err(e) if e.is_no_records_found() => Ok(false),
+        Err(e) => Err(dns_error(&e)),
+    }
+}
+
+fn dns_error(error: &hickory_resolver::ResolveError) -> CheckResult {
+    CheckResult::Uncertain(UncertaintyReason::DnsResolverError(error.to_string()))
+}
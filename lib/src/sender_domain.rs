@@ -0,0 +1,90 @@
+use hickory_resolver::{Name, TokioResolver};
+
+use crate::{CheckResult, Client, Domain, FailureReason, UncertaintyReason};
+
+/// Authentication policies published by a sender domain,
+/// see [`Client::sender_domain_health`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SenderDomainHealth {
+    /// SPF policies of the domain (TXT records starting with `v=spf1`)
+    /// as per [RFC7208](https://www.rfc-editor.org/rfc/rfc7208)
+    pub spf: Vec<String>,
+    /// DMARC policies of the domain (TXT records of `_dmarc.` starting with `v=DMARC1`)
+    /// as per [RFC7489](https://www.rfc-editor.org/rfc/rfc7489)
+    pub dmarc: Vec<String>,
+}
+
+impl SenderDomainHealth {
+    /// Whether the domain publishes exactly one SPF and one DMARC policy.
+    /// Several policies of the same kind are an error which fails the checks of receivers.
+    /// The policies themselves aren't evaluated, so the IP address we connect from
+    /// might still not be permitted to send for the domain.
+    #[must_use]
+    pub fn passes_basic_checks(&self) -> bool {
+        self.spf.len() == 1 && self.dmarc.len() == 1
+    }
+}
+
+impl Client {
+    /// Look up the SPF and DMARC policies of a domain, e.g. to choose one of the
+    /// [`Config::sender_addresses`](crate::Config::sender_addresses). Mail servers commonly
+    /// reject senders of domains without these policies, which results in
+    /// [`UncertaintyReason::SenderRejected`]. Only DNS is queried.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FailureReason::InvalidAddressFormat`] if the domain is invalid
+    /// and [`UncertaintyReason::DnsResolverError`] if the lookup fails
+    pub async fn sender_domain_health(
+        &self,
+        domain: &str,
+    ) -> Result<SenderDomainHealth, CheckResult> {
+        let invalid = || CheckResult::Failure(FailureReason::InvalidAddressFormat);
+        let domain = Domain::new(domain)
+            .filter(|domain| !domain.is_literal())
+            .ok_or_else(invalid)?;
+        let name = Name::from_ascii(format!("{}.", domain.ascii())).map_err(|_| invalid())?;
+        let dmarc_name = Name::from_ascii("_dmarc")
+            .and_then(|label| label.append_domain(&name))
+            .map_err(|_| invalid())?;
+
+        Ok(SenderDomainHealth {
+            spf: policies(&self.resolver, name, "v=spf1").await?,
+            dmarc: policies(&self.resolver, dmarc_name, "v=DMARC1").await?,
+        })
+    }
+}
+
+/// TXT records of the name starting with the version tag, ignoring case
+async fn policies(
+    resolver: &TokioResolver,
+    name: Name,
+    version: &str,
+) -> Result<Vec<String>, CheckResult> {
+    let lookup = match resolver.txt_lookup(name).await {
+        Ok(lookup) => lookup,
+        Err(e) if e.is_no_records_found() => return Ok(vec![]),
+        Err(e) => {
+            return Err(CheckResult::Uncertain(UncertaintyReason::DnsResolverError(
+                e.to_string(),
+            )));
+        }
+    };
+
+    Ok(lookup
+        .iter()
+        .map(|txt| {
+            // Long records are split into several strings, which are concatenated
+            txt.txt_data()
+                .iter()
+                .map(|data| String::from_utf8_lossy(data))
+                .collect::<String>()
+        })
+        .filter(|record| {
+            record
+                .split([' ', ';'])
+                .next()
+                .is_some_and(|tag| tag.eq_ignore_ascii_case(version))
+        })
+        .collect())
+}
@@ -0,0 +1,279 @@
+use std::{fmt::Display, future, io, pin::Pin, task::Poll, time::Duration};
+
+use async_smtp::{
+    commands::QuitCommand, error::SmtpResult, extension::ClientId, response::Response,
+};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::{Domain, address};
+
+/// Limits on a single response of the mail server, protecting against servers
+/// flooding us with endless responses. Exceeding a limit aborts the check with
+/// [`UncertaintyReason::ProtocolError`](crate::UncertaintyReason::ProtocolError).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResponseLimits {
+    /// Maximum number of lines. Defaults to 100.
+    pub max_lines: usize,
+    /// Maximum size in bytes, including line endings. Defaults to 64 KiB.
+    /// Reply lines are limited to 512 bytes as per
+    /// [RFC5321 section 4.5.3.1.5](https://www.rfc-editor.org/rfc/rfc5321#section-4.5.3.1.5).
+    pub max_bytes: usize,
+    /// Maximum number of lines of the greeting, which are commonly few.
+    /// Also bounded by `max_lines`. Defaults to 20.
+    pub max_greeting_lines: usize,
+    /// Maximum time to wait for the complete greeting, e.g. to abort servers stalling
+    /// with an endless greeting sent line by line. If `None`, only
+    /// [`Config::timeout`](crate::Config::timeout) applies. Defaults to `None`.
+    pub greeting_timeout: Option<Duration>,
+}
+
+impl Default for ResponseLimits {
+    fn default() -> Self {
+        Self {
+            max_lines: 100,
+            max_bytes: 64 * 1024,
+            max_greeting_lines: 20,
+            greeting_timeout: None,
+        }
+    }
+}
+
+/// Error of a response exceeding the [`ResponseLimits`]
+#[derive(Debug)]
+struct LimitExceeded;
+
+impl Display for LimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "response exceeds limits")
+    }
+}
+
+impl std::error::Error for LimitExceeded {}
+
+/// Error of a response which doesn't belong to the command we issued,
+/// e.g. an unsolicited line sent before the command
+#[derive(Debug)]
+struct OutOfOrder;
+
+impl Display for OutOfOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "response out of order")
+    }
+}
+
+impl std::error::Error for OutOfOrder {}
+
+/// Whether reading a response failed because it exceeded the [`ResponseLimits`]
+/// or didn't belong to the command we issued
+pub(crate) fn protocol_violation(error: &io::Error) -> bool {
+    matches!(error.get_ref(), Some(inner) if inner.is::<LimitExceeded>() || inner.is::<OutOfOrder>())
+}
+
+fn out_of_order() -> async_smtp::error::Error {
+    io::Error::new(io::ErrorKind::InvalidData, OutOfOrder).into()
+}
+
+fn limit_exceeded() -> async_smtp::error::Error {
+    io::Error::new(io::ErrorKind::InvalidData, LimitExceeded).into()
+}
+
+/// Minimal SMTP connection.
+/// async-smtp doesn't export its `SmtpStream` and its `SmtpTransport`
+/// always greets with EHLO, so we drive the conversation ourselves.
+pub(crate) struct Connection<S> {
+    stream: S,
+    limits: ResponseLimits,
+}
+
+impl<S: AsyncBufRead + AsyncWrite + Unpin> Connection<S> {
+    pub(crate) const fn new(stream: S, limits: ResponseLimits) -> Self {
+        Self { stream, limits }
+    }
+
+    pub(crate) const fn stream_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Send the given command and read the server response.
+    ///
+    /// We never pipeline commands, so data received before sending the command
+    /// and positive responses other than 250, 251 and 252 can't be a response to it.
+    /// Instead of misreading them as a verdict on the command, they are rejected as out of order.
+    pub(crate) async fn command(&mut self, command: impl Display) -> SmtpResult {
+        self.send(command, &[250, 251, 252]).await
+    }
+
+    /// Send `QUIT`, which the server acknowledges with 221 as per
+    /// [RFC5321 section 4.1.1.10](https://www.rfc-editor.org/rfc/rfc5321#section-4.1.1.10)
+    pub(crate) async fn quit(&mut self) -> SmtpResult {
+        self.send(QuitCommand, &[221]).await
+    }
+
+    /// Send the command and accept only the given positive response codes
+    async fn send(&mut self, command: impl Display, expected: &[u16]) -> SmtpResult {
+        if self.has_unread_data().await {
+            return Err(out_of_order());
+        }
+
+        self.stream
+            .write_all(command.to_string().as_bytes())
+            .await?;
+        self.stream.flush().await?;
+
+        let response = self.read_response().await?;
+        if expected.iter().any(|code| response.has_code(*code)) {
+            Ok(response)
+        } else {
+            Err(out_of_order())
+        }
+    }
+
+    /// Read the greeting of the server, which must be 220 as per
+    /// [RFC5321 section 4.2](https://www.rfc-editor.org/rfc/rfc5321#section-4.2).
+    /// The greeting is bounded by the greeting limits of the [`ResponseLimits`].
+    pub(crate) async fn read_greeting(&mut self) -> SmtpResult {
+        let max_lines = self.limits.max_lines.min(self.limits.max_greeting_lines);
+        let response = match self.limits.greeting_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, self.read_limited(max_lines))
+                .await
+                .map_err(|_| limit_exceeded())??,
+            None => self.read_limited(max_lines).await?,
+        };
+        if response.has_code(220) {
+            Ok(response)
+        } else {
+            Err(out_of_order())
+        }
+    }
+
+    /// Whether the server sent data we didn't read yet, without waiting for any
+    async fn has_unread_data(&mut self) -> bool {
+        future::poll_fn(|cx| match Pin::new(&mut self.stream).poll_fill_buf(cx) {
+            Poll::Ready(Ok(buffer)) => Poll::Ready(!buffer.is_empty()),
+            // Errors and closed connections surface when reading the response
+            Poll::Ready(Err(_)) | Poll::Pending => Poll::Ready(false),
+        })
+        .await
+    }
+
+    /// Read a (possibly multiline) response.
+    /// Negative responses are returned as [`Error::Transient`] or [`Error::Permanent`].
+    pub(crate) async fn read_response(&mut self) -> SmtpResult {
+        self.read_limited(self.limits.max_lines).await
+    }
+
+    /// Read a response of at most the given number of lines
+    async fn read_limited(&mut self, max_lines: usize) -> SmtpResult {
+        let mut buffer = String::new();
+        let mut lines = 0;
+
+        loop {
+            let remaining = self.limits.max_bytes.saturating_sub(buffer.len());
+            let read = (&mut self.stream)
+                .take(remaining as u64)
+                .read_line(&mut buffer)
+                .await?;
+
+            lines += 1;
+            if lines > max_lines || buffer.len() >= self.limits.max_bytes {
+                return Err(limit_exceeded());
+            }
+
+            if read == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "connection closed by server",
+                )
+                .into());
+            }
+
+            // Lines of multiline responses have a hyphen after the code
+            let line = &buffer[buffer.len() - read..];
+            if line.as_bytes().get(3) != Some(&b'-') {
+                break;
+            }
+        }
+
+        let response: Response = buffer.parse()?;
+        if response.is_positive() {
+            Ok(response)
+        } else {
+            Err(response.into())
+        }
+    }
+}
+
+/// HELO command, which isn't provided by async-smtp
+pub(crate) struct HeloCommand(pub(crate) ClientId);
+
+impl Display for HeloCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HELO {}\r\n", self.0)
+    }
+}
+
+/// RCPT command sending the address exactly as validated by
+/// [`parse_address`](crate::parse_address). async-smtp only accepts ASCII addresses without
+/// whitespace, which rejects quoted local parts like `"john doe"` and internationalized
+/// addresses as per [RFC6531](https://www.rfc-editor.org/rfc/rfc6531#section-3.3).
+pub(crate) struct RecipientCommand(String);
+
+impl RecipientCommand {
+    pub(crate) fn new(mailbox: String) -> Self {
+        Self(mailbox)
+    }
+}
+
+/// The mailbox sent on the network, built from the parts of a parsed address.
+/// Returns `None` if the parts contain characters which would break the command.
+/// Whitespace is only allowed within a quoted local part.
+pub(crate) fn mailbox(local_part: &str, domain: &Domain) -> Option<String> {
+    let local_part_is_valid = if address::is_quoted(local_part) {
+        !local_part
+            .chars()
+            .any(|c| c.is_control() || c == '<' || c == '>')
+    } else {
+        is_valid_argument(local_part)
+    };
+
+    // The network always uses the ASCII form of the domain
+    (local_part_is_valid && is_valid_argument(domain.ascii()))
+        .then(|| format!("{local_part}@{}", domain.ascii()))
+}
+
+/// Whether the command argument can't break out of the command
+pub(crate) fn is_valid_argument(argument: &str) -> bool {
+    !argument
+        .chars()
+        .any(|c| c.is_control() || c.is_whitespace() || c == '<' || c == '>')
+}
+
+impl Display for RecipientCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RCPT TO:<{}>\r\n", self.0)
+    }
+}
+
+/// Whether the EHLO response advertises the given extension keyword
+pub(crate) fn supports(ehlo: &Response, keyword: &str) -> bool {
+    ehlo.message.iter().skip(1).any(|line| {
+        line.split_whitespace()
+            .next()
+            .is_some_and(|word| word.eq_ignore_ascii_case(keyword))
+    })
+}
+
+/// Maximum message size advertised by the `SIZE` extension in the EHLO response as per
+/// [RFC1870](https://www.rfc-editor.org/rfc/rfc1870#section-4).
+/// A limit of 0 means that there is no fixed maximum.
+/// Returns `None` if the extension isn't advertised.
+pub(crate) fn size_limit(ehlo: &Response) -> Option<usize> {
+    ehlo.message.iter().skip(1).find_map(|line| {
+        let mut words = line.split_whitespace();
+        if !words.next()?.eq_ignore_ascii_case("SIZE") {
+            return None;
+        }
+
+        Some(words.next().and_then(|size| size.parse().ok()).unwrap_or(0))
+    })
+}
@@ -0,0 +1,57 @@
+/// A way of asking the mail server whether an address exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Ask with `VRFY` as per [RFC5321 section 3.5](https://www.rfc-editor.org/rfc/rfc5321#section-3.5).
+    /// Most servers disable `VRFY` or reply with 252 (cannot verify, but will accept),
+    /// in which case the next strategy is tried. A confirmed or rejected address is definitive.
+    Vrfy,
+    /// Announce the recipient with `RCPT TO`. The response is always definitive.
+    Rcpt,
+    /// Once the address was accepted, probe if the domain accepts any address
+    /// by announcing a random recipient, see [`Config::probe_local_part`](crate::Config::probe_local_part).
+    /// The outcome is reported in [`DetailedResult::catch_all`](crate::DetailedResult::catch_all).
+    /// Skipped for [`Config::trusted_providers`](crate::Config::trusted_providers).
+    CatchAllProbe,
+}
+
+/// Strategies the [`Client`](crate::Client) executes in order.
+///
+/// Strategies checking the address ([`Strategy::Vrfy`] and [`Strategy::Rcpt`]) are skipped
+/// once the address was accepted, and a rejection ends the check right away.
+/// [`Strategy::CatchAllProbe`] only runs after the address was accepted.
+/// If no strategy could determine the result, it's [`UncertaintyReason::Inconclusive`](crate::UncertaintyReason::Inconclusive).
+///
+/// The default chain only consists of [`Strategy::Rcpt`].
+/// For example `[Vrfy, Rcpt, CatchAllProbe]` tries `VRFY` first, falls back on `RCPT TO`
+/// if it's unsupported and finally probes for a catch-all domain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrategyChain(pub Vec<Strategy>);
+
+impl StrategyChain {
+    #[must_use]
+    pub fn new(strategies: Vec<Strategy>) -> Self {
+        Self(strategies)
+    }
+}
+
+impl Default for StrategyChain {
+    fn default() -> Self {
+        Self(vec![Strategy::Rcpt])
+    }
+}
+
+/// How much of the [`StrategyChain`] is executed,
+/// see [`Config::chain_mode`](crate::Config::chain_mode)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChainMode {
+    /// Stop at the first definitive result as described in [`StrategyChain`]
+    #[default]
+    ShortCircuit,
+    /// Execute every strategy checking the address, even after it was accepted or rejected,
+    /// e.g. to investigate servers whose `VRFY` and `RCPT TO` responses disagree.
+    /// The results are combined with [`CheckResult::merge`](crate::CheckResult::merge)
+    /// and reported individually in
+    /// [`DetailedResult::strategy_results`](crate::DetailedResult::strategy_results).
+    /// Connection problems and timeouts still end the check.
+    Exhaustive,
+}
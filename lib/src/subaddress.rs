@@ -0,0 +1,68 @@
+use crate::Domain;
+
+/// Providers supporting subaddresses (e.g. `user+tag@gmail.com`) as per
+/// [RFC5233](https://www.rfc-editor.org/rfc/rfc5233), with their separator.
+/// Providers are only listed if everything after the separator is guaranteed to be ignored,
+/// which isn't the case for all providers using a separator (e.g. Yahoo's disposable addresses).
+const SEPARATORS: &[(&str, char)] = &[
+    ("gmail.com", '+'),
+    ("googlemail.com", '+'),
+    ("outlook.com", '+'),
+    ("hotmail.com", '+'),
+    ("live.com", '+'),
+    ("icloud.com", '+'),
+    ("me.com", '+'),
+    ("protonmail.com", '+'),
+    ("protonmail.ch", '+'),
+    ("proton.me", '+'),
+    ("pm.me", '+'),
+    ("fastmail.com", '+'),
+];
+
+/// Local part of the mailbox the address is delivered to, without the subaddress.
+/// Local parts of unknown providers and quoted local parts are returned unchanged.
+pub(crate) fn strip<'a>(local_part: &'a str, domain: &Domain) -> &'a str {
+    if local_part.starts_with('"') {
+        return local_part;
+    }
+
+    let Some((_, separator)) = SEPARATORS
+        .iter()
+        .find(|(provider, _)| domain.matches(provider))
+    else {
+        return local_part;
+    };
+
+    match local_part.split_once(*separator) {
+        Some((mailbox, _)) if !mailbox.is_empty() => mailbox,
+        _ => local_part,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::strip;
+    use crate::Domain;
+
+    #[test]
+    fn gmail() {
+        let gmail = Domain::new("Gmail.com").unwrap();
+        assert_eq!(strip("thomas.zahner+mailify", &gmail), "thomas.zahner");
+        assert_eq!(strip("thomas+a+b", &gmail), "thomas");
+        assert_eq!(strip("thomas", &gmail), "thomas");
+        assert_eq!(strip("+tag", &gmail), "+tag");
+        assert_eq!(strip(r#""thomas+tag""#, &gmail), r#""thomas+tag""#);
+    }
+
+    #[test]
+    fn idn_and_case() {
+        let proton = Domain::new("ProtonMail.CH.").unwrap();
+        assert_eq!(strip("Thomas+Tag", &proton), "Thomas");
+    }
+
+    #[test]
+    fn unknown_provider() {
+        let domain = Domain::new("example.com").unwrap();
+        assert_eq!(strip("thomas+tag", &domain), "thomas+tag");
+    }
+}
@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+use crate::Phase;
+
+/// Time spent in each [`Phase`] of a check, to find out where latency comes from.
+/// Phases which weren't reached are zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timings {
+    /// Resolving the mail server of the domain
+    pub dns: Duration,
+    /// Establishing the TCP connection
+    pub connect: Duration,
+    /// Waiting for the greeting of the server
+    pub greeting: Duration,
+    /// EHLO or HELO
+    pub hello: Duration,
+    /// MAIL FROM, including retries with other senders
+    pub mail_from: Duration,
+    /// VRFY
+    pub vrfy: Duration,
+    /// RCPT TO
+    pub rcpt: Duration,
+    /// Catch-all probe
+    pub catch_all_probe: Duration,
+    /// Whole check, including address validation and [`Config::fallback`](crate::Config::fallback)
+    pub total: Duration,
+}
+
+impl Timings {
+    /// Time spent in the given phase
+    #[must_use]
+    pub const fn phase(&self, phase: Phase) -> Duration {
+        match phase {
+            Phase::Dns => self.dns,
+            Phase::Connect => self.connect,
+            Phase::Greeting => self.greeting,
+            Phase::Hello => self.hello,
+            Phase::MailFrom => self.mail_from,
+            Phase::Vrfy => self.vrfy,
+            Phase::Rcpt => self.rcpt,
+            Phase::CatchAllProbe => self.catch_all_probe,
+        }
+    }
+
+    pub(crate) fn add(&mut self, phase: Phase, elapsed: Duration) {
+        let timing = match phase {
+            Phase::Dns => &mut self.dns,
+            Phase::Connect => &mut self.connect,
+            Phase::Greeting => &mut self.greeting,
+            Phase::Hello => &mut self.hello,
+            Phase::MailFrom => &mut self.mail_from,
+            Phase::Vrfy => &mut self.vrfy,
+            Phase::Rcpt => &mut self.rcpt,
+            Phase::CatchAllProbe => &mut self.catch_all_probe,
+        };
+        *timing += elapsed;
+    }
+}
@@ -0,0 +1,150 @@
+use std::{
+    fmt::Display,
+    io,
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+    task::{Context, Poll},
+};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// Whether a transcript line was sent by us or received from the server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Command sent to the mail server
+    Sent,
+    /// Response line received from the mail server
+    Received,
+}
+
+/// A single line of the SMTP conversation, without the trailing CRLF
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscriptLine {
+    /// Who sent the line
+    pub direction: Direction,
+    /// Line content
+    pub line: String,
+}
+
+impl Display for TranscriptLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let prefix = match self.direction {
+            Direction::Sent => ">>",
+            Direction::Received => "<<",
+        };
+
+        write!(f, "{prefix} {}", self.line)
+    }
+}
+
+/// Shared recording of an SMTP conversation.
+/// Shared so that lines recorded before a timeout aren't lost.
+#[derive(Clone, Default)]
+pub(crate) struct Transcript(Arc<Mutex<Recording>>);
+
+#[derive(Default)]
+struct Recording {
+    lines: Vec<TranscriptLine>,
+    sent: Vec<u8>,
+    received: Vec<u8>,
+}
+
+impl Transcript {
+    fn record(&self, direction: Direction, bytes: &[u8]) {
+        let mut recording = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let Recording {
+            lines,
+            sent,
+            received,
+        } = &mut *recording;
+
+        let pending = match direction {
+            Direction::Sent => sent,
+            Direction::Received => received,
+        };
+
+        pending.extend_from_slice(bytes);
+        while let Some(end) = pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=end).collect();
+            lines.push(TranscriptLine {
+                direction,
+                line: String::from_utf8_lossy(&line).trim_end().to_string(),
+            });
+        }
+    }
+
+    /// Append the complete lines of another transcript
+    fn extend(&self, other: &Transcript) {
+        let lines = other.lines();
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .lines
+            .extend(lines);
+    }
+
+    /// All complete lines recorded so far
+    pub(crate) fn lines(&self) -> Vec<TranscriptLine> {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .lines
+            .clone()
+    }
+}
+
+/// Stream wrapper recording all traffic into a [`Transcript`]
+pub(crate) struct Recorder<S> {
+    inner: S,
+    transcript: Transcript,
+}
+
+impl<S> Recorder<S> {
+    pub(crate) const fn new(inner: S, transcript: Transcript) -> Self {
+        Self { inner, transcript }
+    }
+
+    /// Continue recording into `transcript`, starting with the lines recorded so far
+    pub(crate) fn record_into(&mut self, transcript: &Transcript) {
+        transcript.extend(&self.transcript);
+        self.transcript = transcript.clone();
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for Recorder<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let before = buf.filled().len();
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = poll {
+            self.transcript
+                .record(Direction::Received, &buf.filled()[before..]);
+        }
+        poll
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for Recorder<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut self.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(written)) = poll {
+            self.transcript.record(Direction::Sent, &buf[..written]);
+        }
+        poll
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
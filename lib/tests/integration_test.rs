@@ -8,7 +8,10 @@ use tokio_util::codec::{Framed, LinesCodec};
 const PORT: u16 = 2525;
 
 async fn listen<R: Into<RequestResponseList>>(list: R) {
-    let addr = SocketAddr::from(([127, 0, 0, 1], PORT));
+    listen_on(SocketAddr::from(([127, 0, 0, 1], PORT)), list).await;
+}
+
+async fn listen_on<R: Into<RequestResponseList>>(addr: SocketAddr, list: R) {
     let listener = TcpListener::bind(addr).await.unwrap();
 
     match listener.accept().await {
@@ -17,6 +20,40 @@ async fn listen<R: Into<RequestResponseList>>(list: R) {
     }
 }
 
+/// Accept a connection and greet only after the delay
+async fn greet_after<R: Into<RequestResponseList>>(delay: std::time::Duration, list: R) {
+    let addr = SocketAddr::from(([127, 0, 0, 1], PORT));
+    let listener = TcpListener::bind(addr).await.unwrap();
+
+    let (stream, _) = listener.accept().await.unwrap();
+    tokio::time::sleep(delay).await;
+    handle_connection(stream, list.into()).await;
+}
+
+/// Accept a connection and close it without greeting, optionally with a TCP reset
+async fn accept_and_close(reset: bool) {
+    let addr = SocketAddr::from(([127, 0, 0, 1], PORT));
+    let listener = TcpListener::bind(addr).await.unwrap();
+
+    let (stream, _) = listener.accept().await.unwrap();
+    if reset {
+        // Closing with a zero linger time sends RST instead of FIN
+        stream.set_linger(Some(std::time::Duration::ZERO)).unwrap();
+    }
+}
+
+/// Accept a connection and send an endless multiline greeting, a line every `delay`
+async fn greet_endlessly(delay: std::time::Duration) {
+    let addr = SocketAddr::from(([127, 0, 0, 1], PORT));
+    let listener = TcpListener::bind(addr).await.unwrap();
+
+    let (mut stream, _) = listener.accept().await.unwrap();
+    // Writing fails once the client closed the connection
+    while stream.write_all(b"220-Please wait\r\n").await.is_ok() {
+        tokio::time::sleep(delay).await;
+    }
+}
+
 async fn handle_connection(mut stream: TcpStream, list: RequestResponseList) {
     let (_, writer) = stream.split();
     let mut writer = BufWriter::new(writer);
@@ -30,9 +67,15 @@ async fn handle_connection(mut stream: TcpStream, list: RequestResponseList) {
 async fn handle_session(stream: TcpStream, mut list: RequestResponseList) {
     let mut framed = Framed::new(stream, LinesCodec::new());
     while let Some(line_str) = framed.next().await {
-        let line = line_str.unwrap();
+        // Clients closing the connection with unread data reset it
+        let Ok(line) = line_str else {
+            break;
+        };
         let response = list.get_next(line);
-        send_commands(&mut framed, vec![response]).await;
+        // An empty response simulates a server which stopped responding
+        if !response.is_empty() {
+            send_commands(&mut framed, vec![response]).await;
+        }
     }
 }
 async fn send_commands(framed: &mut Framed<TcpStream, LinesCodec>, commands: Vec<String>) {
@@ -55,7 +98,7 @@ impl RequestResponseList {
             panic!("Expected no more requests but received '{actual}'");
         };
 
-        if expected.request != actual {
+        if !expected.matches(&actual) {
             panic!("Expected request '{}' but got '{actual}'", expected.request,);
         }
 
@@ -68,6 +111,20 @@ struct RequestResponse {
     response: String,
 }
 
+impl RequestResponse {
+    /// A `*` in the expected request matches any text
+    fn matches(&self, actual: &str) -> bool {
+        match self.request.split_once('*') {
+            Some((prefix, suffix)) => {
+                actual.len() >= prefix.len() + suffix.len()
+                    && actual.starts_with(prefix)
+                    && actual.ends_with(suffix)
+            }
+            None => self.request == actual,
+        }
+    }
+}
+
 impl From<(&str, &str)> for RequestResponse {
     fn from((request, response): (&str, &str)) -> Self {
         Self {
@@ -79,13 +136,26 @@ impl From<(&str, &str)> for RequestResponse {
 
 #[cfg(test)]
 mod tests {
+    use async_smtp::extension::{MailBodyParameter, MailParameter};
     use serial_test::serial;
-    use std::time::Duration;
+    use std::{collections::HashMap, sync::Arc, time::Duration};
 
-    use tokio::{task, time::sleep};
+    use tokio::{
+        net::TcpListener,
+        task,
+        time::{Instant, sleep},
+    };
 
-    use crate::{PORT, RequestResponseList, listen};
-    use mailify_lib::{CheckResult, Client, Config, FailureReason, UncertaintyReason};
+    use crate::{
+        PORT, RequestResponseList, SocketAddr, accept_and_close, greet_after, greet_endlessly,
+        listen, listen_on,
+    };
+    use mailify_lib::{
+        BodyParameter, BoxFuture, ChainMode, CheckResult, ClassificationRule, Client,
+        CommandSequence, Config, ConfigOverrides, DetailedResult, Direction, FailureReason, Phase,
+        PostmasterStatus, ProbeLocalPart, ResponseLimits, Strategy, StrategyChain, TranscriptLine,
+        UncertaintyReason, VerificationBackend,
+    };
 
     /// Default template for expected requets
     /// with their associated response
@@ -93,7 +163,6 @@ mod tests {
         ($final_message:expr) => {
             RequestResponseList::from(
                 [
-                    ("EHLO [127.0.0.1]", "250 OK"),
                     ("EHLO example.com.", "250 OK"),
                     ("MAIL FROM:<me@thomaszahner.ch>", "250 OK"),
                     $final_message,
@@ -104,6 +173,15 @@ mod tests {
     }
 
     async fn check(address: &str, list: RequestResponseList, expected: CheckResult) {
+        check_with_config(address, list, expected, Config::default()).await;
+    }
+
+    async fn check_with_config(
+        address: &str,
+        list: RequestResponseList,
+        expected: CheckResult,
+        config: Config,
+    ) {
         let server = task::spawn(async move {
             listen(list).await;
         });
@@ -112,8 +190,9 @@ mod tests {
 
         let result = Client::new(Config {
             port: PORT,
-            ..Default::default()
+            ..config
         })
+        .unwrap()
         .check(address)
         .await;
         assert_eq!(result, expected);
@@ -132,6 +211,67 @@ mod tests {
         .await;
     }
 
+    #[serial]
+    #[tokio::test]
+    async fn mailto() {
+        check(
+            "mailto:hello@[127.0.0.1]?subject=Hello%20there",
+            default_template!(("RCPT TO:<hello@[127.0.0.1]>", "250 OK")),
+            CheckResult::Success,
+        )
+        .await;
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn address_literals() {
+        // The literal is normalized, whichever form the user typed
+        let server = task::spawn(listen_on(
+            SocketAddr::from((std::net::Ipv6Addr::LOCALHOST, PORT)),
+            default_template!(("RCPT TO:<hello@[IPv6:::1]>", "250 OK")),
+        ));
+        sleep(Duration::from_millis(100)).await;
+        let client = Client::new(Config {
+            port: PORT,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(
+            client.check("hello@[ipv6:0:0:0:0:0:0:0:1]").await,
+            CheckResult::Success
+        );
+        server.await.unwrap();
+
+        check(
+            "hello@[127.0.0.1]",
+            default_template!(("RCPT TO:<hello@[127.0.0.1]>", "250 OK")),
+            CheckResult::Success,
+        )
+        .await;
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn quoted_local_part() {
+        check(
+            r#""john doe"@[127.0.0.1]"#,
+            default_template!((r#"RCPT TO:<"john doe"@[127.0.0.1]>"#, "250 OK")),
+            CheckResult::Success,
+        )
+        .await;
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn quoted_local_part_with_at_sign() {
+        check(
+            r#""john@doe"@[127.0.0.1]"#,
+            default_template!((r#"RCPT TO:<"john@doe"@[127.0.0.1]>"#, "250 OK")),
+            CheckResult::Success,
+        )
+        .await;
+    }
+
     #[serial]
     #[tokio::test]
     async fn no_such_address() {
@@ -153,4 +293,1782 @@ mod tests {
         )
         .await;
     }
+
+    #[serial]
+    #[tokio::test]
+    async fn greylisting() {
+        check(
+            "hello@[127.0.0.1]",
+            default_template!(("RCPT TO:<hello@[127.0.0.1]>", "450 4.2.0 <hello@[127.0.0.1]>: Recipient address rejected: Greylisted, see https://postgrey.schweikert.ch/help/example.com.html")),
+            CheckResult::Uncertain(UncertaintyReason::Greylisted),
+        )
+        .await;
+
+        check(
+            "hello@[127.0.0.1]",
+            default_template!((
+                "RCPT TO:<hello@[127.0.0.1]>",
+                "451 4.7.1 Please try again later"
+            )),
+            CheckResult::Uncertain(UncertaintyReason::Greylisted),
+        )
+        .await;
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn throttling() {
+        check(
+            "hello@[127.0.0.1]",
+            default_template!((
+                "RCPT TO:<hello@[127.0.0.1]>",
+                "452 4.3.2 Connection rate limit exceeded"
+            )),
+            CheckResult::Uncertain(UncertaintyReason::Throttled),
+        )
+        .await;
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn temporary_local_error() {
+        check(
+            "hello@[127.0.0.1]",
+            default_template!((
+                "RCPT TO:<hello@[127.0.0.1]>",
+                "451 Requested action aborted: local error in processing"
+            )),
+            CheckResult::Uncertain(UncertaintyReason::ServerError),
+        )
+        .await;
+    }
+
+    fn with_senders(senders: &[&str]) -> Config {
+        let senders = senders.iter().map(ToString::to_string).collect();
+        Config::default()
+            .with_sender_addresses(senders)
+            .ok()
+            .unwrap()
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn sender_fallback() {
+        check_with_config(
+            "hello@[127.0.0.1]",
+            RequestResponseList::from(
+                [
+                    ("EHLO example.com.", "250 OK"),
+                    (
+                        "MAIL FROM:<a@nullmx.example>",
+                        "550 5.7.27 Sender address has null MX",
+                    ),
+                    ("RSET", "250 OK"),
+                    ("MAIL FROM:<b@example.org>", "250 OK"),
+                    ("RCPT TO:<hello@[127.0.0.1]>", "250 OK"),
+                ]
+                .as_slice(),
+            ),
+            CheckResult::Success,
+            with_senders(&["a@nullmx.example", "b@example.org"]),
+        )
+        .await;
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn all_senders_rejected() {
+        check_with_config(
+            "hello@[127.0.0.1]",
+            RequestResponseList::from(
+                [
+                    ("EHLO example.com.", "250 OK"),
+                    (
+                        "MAIL FROM:<a@example.org>",
+                        "550 5.7.23 SPF validation failed",
+                    ),
+                    ("RSET", "250 OK"),
+                    (
+                        "MAIL FROM:<b@example.org>",
+                        "450 4.1.8 Sender address rejected: Domain not found",
+                    ),
+                ]
+                .as_slice(),
+            ),
+            CheckResult::Uncertain(UncertaintyReason::SenderRejected),
+            with_senders(&["a@example.org", "b@example.org"]),
+        )
+        .await;
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn transcript() {
+        let server = task::spawn(async move {
+            listen(default_template!(("RCPT TO:<hello@[127.0.0.1]>", "250 OK"))).await;
+        });
+
+        sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+        let result = Client::new(Config {
+            port: PORT,
+            ..Default::default()
+        })
+        .unwrap()
+        .check_detailed("hello@[127.0.0.1]")
+        .await;
+        server.await.unwrap();
+
+        let sent = |line: &str| TranscriptLine {
+            direction: Direction::Sent,
+            line: line.into(),
+        };
+        let received = |line: &str| TranscriptLine {
+            direction: Direction::Received,
+            line: line.into(),
+        };
+
+        assert_eq!(result.result, CheckResult::Success);
+        assert_eq!(
+            result.transcript,
+            vec![
+                received("220 SMTP server mock"),
+                sent("EHLO example.com."),
+                received("250 OK"),
+                sent("MAIL FROM:<me@thomaszahner.ch>"),
+                received("250 OK"),
+                sent("RCPT TO:<hello@[127.0.0.1]>"),
+                received("250 OK"),
+            ]
+        );
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn helo_fallback() {
+        check(
+            "hello@[127.0.0.1]",
+            RequestResponseList::from(
+                [
+                    ("EHLO example.com.", "502 Command not implemented"),
+                    ("HELO example.com.", "250 OK"),
+                    ("MAIL FROM:<me@thomaszahner.ch>", "250 OK"),
+                    ("RCPT TO:<hello@[127.0.0.1]>", "250 OK"),
+                ]
+                .as_slice(),
+            ),
+            CheckResult::Success,
+        )
+        .await;
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn syntax_error() {
+        check(
+            "hello@[127.0.0.1]",
+            default_template!((
+                "RCPT TO:<hello@[127.0.0.1]>",
+                "500 5.5.2 Syntax error, command unrecognized"
+            )),
+            CheckResult::Uncertain(UncertaintyReason::ProtocolError),
+        )
+        .await;
+
+        check(
+            "hello@[127.0.0.1]",
+            default_template!((
+                "RCPT TO:<hello@[127.0.0.1]>",
+                "501 Syntax error in parameters or arguments"
+            )),
+            CheckResult::Uncertain(UncertaintyReason::ProtocolError),
+        )
+        .await;
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn bad_sequence() {
+        check(
+            "hello@[127.0.0.1]",
+            default_template!((
+                "RCPT TO:<hello@[127.0.0.1]>",
+                "503 5.5.1 Error: authentication required"
+            )),
+            CheckResult::Uncertain(UncertaintyReason::ProtocolError),
+        )
+        .await;
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn policy_rejection() {
+        check(
+            "hello@[127.0.0.1]",
+            default_template!((
+                "RCPT TO:<hello@[127.0.0.1]>",
+                "550 5.7.1 Recipient address rejected: Access denied by policy"
+            )),
+            CheckResult::Uncertain(UncertaintyReason::PolicyRejection),
+        )
+        .await;
+
+        // Policy rejections of the sender are retried with the next sender
+        check(
+            "hello@[127.0.0.1]",
+            RequestResponseList::from(
+                [
+                    ("EHLO example.com.", "250 OK"),
+                    (
+                        "MAIL FROM:<me@thomaszahner.ch>",
+                        "550 5.7.1 Sender address rejected: Not permitted by policy",
+                    ),
+                ]
+                .as_slice(),
+            ),
+            CheckResult::Uncertain(UncertaintyReason::SenderRejected),
+        )
+        .await;
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn polite_command_sequence() {
+        let server = task::spawn(async move {
+            listen(RequestResponseList::from(
+                [
+                    ("EHLO example.com.", "250 OK"),
+                    ("NOOP", "250 OK"),
+                    ("MAIL FROM:<me@thomaszahner.ch>", "250 OK"),
+                    ("RCPT TO:<hello@[127.0.0.1]>", "250 OK"),
+                    ("RSET", "250 OK"),
+                ]
+                .as_slice(),
+            ))
+            .await;
+        });
+
+        sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+        let result = Client::new(Config {
+            port: PORT,
+            command_sequence: CommandSequence::Polite,
+            ..Default::default()
+        })
+        .unwrap()
+        .check_detailed("hello@[127.0.0.1]")
+        .await;
+        server.await.unwrap();
+
+        let sent: Vec<_> = result
+            .transcript
+            .into_iter()
+            .filter(|line| line.direction == Direction::Sent)
+            .map(|line| line.line)
+            .collect();
+
+        assert_eq!(result.result, CheckResult::Success);
+        assert_eq!(
+            sent,
+            [
+                "EHLO example.com.",
+                "NOOP",
+                "MAIL FROM:<me@thomaszahner.ch>",
+                "RCPT TO:<hello@[127.0.0.1]>",
+                "RSET",
+            ]
+        );
+
+        // The recipient is reset after a rejection as well
+        let server = task::spawn(async move {
+            listen(RequestResponseList::from(
+                [
+                    ("EHLO example.com.", "250 OK"),
+                    ("NOOP", "250 OK"),
+                    ("MAIL FROM:<me@thomaszahner.ch>", "250 OK"),
+                    ("RCPT TO:<hello@[127.0.0.1]>", "550 5.1.1 No such user"),
+                    ("RSET", "250 OK"),
+                ]
+                .as_slice(),
+            ))
+            .await;
+        });
+
+        sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+        let result = Client::new(Config {
+            port: PORT,
+            command_sequence: CommandSequence::Polite,
+            ..Default::default()
+        })
+        .unwrap()
+        .check_detailed("hello@[127.0.0.1]")
+        .await;
+        server.await.unwrap();
+
+        assert_eq!(
+            result.result,
+            CheckResult::Failure(FailureReason::NoSuchAddress)
+        );
+        assert_eq!(
+            result.transcript.last().map(|line| line.line.as_str()),
+            Some("250 OK")
+        );
+        assert!(
+            result
+                .transcript
+                .iter()
+                .any(|line| line.direction == Direction::Sent && line.line == "RSET")
+        );
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn mail_parameters() {
+        check_with_config(
+            "hello@[127.0.0.1]",
+            RequestResponseList::from(
+                [
+                    ("EHLO example.com.", "250 OK"),
+                    ("MAIL FROM:<me@thomaszahner.ch> BODY=7BIT SIZE=0", "250 OK"),
+                    ("RCPT TO:<hello@[127.0.0.1]>", "250 OK"),
+                ]
+                .as_slice(),
+            ),
+            CheckResult::Success,
+            Config {
+                mail_parameters: vec![
+                    MailParameter::Body(MailBodyParameter::SevenBit),
+                    MailParameter::Size(0),
+                ],
+                ..Default::default()
+            },
+        )
+        .await;
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn sender_override() {
+        let server = task::spawn(async move {
+            listen(RequestResponseList::from(
+                [
+                    ("EHLO example.com.", "250 OK"),
+                    ("MAIL FROM:<other@example.org>", "250 OK"),
+                    ("RCPT TO:<hello@[127.0.0.1]>", "250 OK"),
+                ]
+                .as_slice(),
+            ))
+            .await;
+            listen(default_template!(("RCPT TO:<hello@[127.0.0.1]>", "250 OK"))).await;
+        });
+
+        sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+        let client = Client::new(Config {
+            port: PORT,
+            ..Default::default()
+        })
+        .unwrap();
+        let overrides = ConfigOverrides::default()
+            .with_sender_address("other@example.org".into())
+            .unwrap();
+
+        let overridden = client.check_with("hello@[127.0.0.1]", overrides).await;
+        sleep(Duration::from_millis(100)).await; // Wait for the server to listen again
+        // The override doesn't outlive the check
+        let unchanged = client.check("hello@[127.0.0.1]").await;
+        server.await.unwrap();
+
+        assert_eq!(overridden, CheckResult::Success);
+        assert_eq!(unchanged, CheckResult::Success);
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn postmaster() {
+        let server = task::spawn(async move {
+            listen(default_template!((
+                "RCPT TO:<postmaster@[127.0.0.1]>",
+                "250 OK"
+            )))
+            .await;
+            listen(default_template!((
+                "RCPT TO:<postmaster@[127.0.0.1]>",
+                "550 5.1.1 User unknown"
+            )))
+            .await;
+        });
+
+        sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+        let client = Client::new(Config {
+            port: PORT,
+            ..Default::default()
+        })
+        .unwrap();
+        let accepted = client.verify_postmaster("[127.0.0.1]").await;
+        sleep(Duration::from_millis(100)).await; // Wait for the server to listen again
+        let rejected = client.verify_postmaster("[127.0.0.1]").await;
+        server.await.unwrap();
+
+        assert_eq!(accepted, PostmasterStatus::Accepted);
+        assert_eq!(rejected, PostmasterStatus::Rejected);
+        assert_eq!(
+            client.verify_postmaster("invalid domain").await,
+            PostmasterStatus::Unknown(CheckResult::Failure(FailureReason::InvalidAddressFormat))
+        );
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn out_of_order_responses() {
+        // An unsolicited line must not be mistaken for the response to RCPT TO
+        check(
+            "hello@[127.0.0.1]",
+            RequestResponseList::from(
+                [
+                    ("EHLO example.com.", "250 OK"),
+                    (
+                        "MAIL FROM:<me@thomaszahner.ch>",
+                        "250 OK\r\n250 2.1.5 Recipient OK",
+                    ),
+                ]
+                .as_slice(),
+            ),
+            CheckResult::Uncertain(UncertaintyReason::ProtocolError),
+        )
+        .await;
+
+        // A greeting isn't a response to EHLO
+        check(
+            "hello@[127.0.0.1]",
+            RequestResponseList::from([("EHLO example.com.", "220 SMTP server mock")].as_slice()),
+            CheckResult::Uncertain(UncertaintyReason::ProtocolError),
+        )
+        .await;
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn response_flood() {
+        let many_lines = format!("{}250 OK", "250-flood\r\n".repeat(1000));
+        let long_line = format!("250 {}", "a".repeat(100_000));
+
+        for response in [&many_lines, &long_line] {
+            check(
+                "hello@[127.0.0.1]",
+                RequestResponseList::from([("EHLO example.com.", response.as_str())].as_slice()),
+                CheckResult::Uncertain(UncertaintyReason::ProtocolError),
+            )
+            .await;
+        }
+
+        // Responses within the limits are fine
+        let config = Config {
+            response_limits: ResponseLimits {
+                max_lines: 1001,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        check_with_config(
+            "hello@[127.0.0.1]",
+            RequestResponseList::from(
+                [
+                    ("EHLO example.com.", many_lines.as_str()),
+                    ("MAIL FROM:<me@thomaszahner.ch>", "250 OK"),
+                    ("RCPT TO:<hello@[127.0.0.1]>", "250 OK"),
+                ]
+                .as_slice(),
+            ),
+            CheckResult::Success,
+            config,
+        )
+        .await;
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn size_extension() {
+        check(
+            "hello@[127.0.0.1]",
+            RequestResponseList::from(
+                [
+                    ("EHLO example.com.", "250-mock\r\n250 SIZE 1000"),
+                    ("MAIL FROM:<me@thomaszahner.ch> SIZE=0", "250 OK"),
+                    ("RCPT TO:<hello@[127.0.0.1]>", "250 OK"),
+                ]
+                .as_slice(),
+            ),
+            CheckResult::Success,
+        )
+        .await;
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn body_parameter() {
+        let list = |mail_from| {
+            RequestResponseList::from(
+                [
+                    ("EHLO example.com.", "250-mock\r\n250 8BITMIME"),
+                    (mail_from, "250 OK"),
+                    ("RCPT TO:<hello@[127.0.0.1]>", "250 OK"),
+                ]
+                .as_slice(),
+            )
+        };
+
+        check(
+            "hello@[127.0.0.1]",
+            list("MAIL FROM:<me@thomaszahner.ch> BODY=8BITMIME"),
+            CheckResult::Success,
+        )
+        .await;
+
+        check_with_config(
+            "hello@[127.0.0.1]",
+            list("MAIL FROM:<me@thomaszahner.ch>"),
+            CheckResult::Success,
+            Config {
+                body_parameter: BodyParameter::Omit,
+                ..Default::default()
+            },
+        )
+        .await;
+
+        // Not advertised
+        check(
+            "hello@[127.0.0.1]",
+            default_template!(("RCPT TO:<hello@[127.0.0.1]>", "250 OK")),
+            CheckResult::Success,
+        )
+        .await;
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn size_exceeded() {
+        check(
+            "hello@[127.0.0.1]",
+            RequestResponseList::from(
+                [
+                    ("EHLO example.com.", "250-mock\r\n250 SIZE"),
+                    (
+                        "MAIL FROM:<me@thomaszahner.ch> SIZE=0",
+                        "552 5.3.4 Message size exceeds fixed maximum message size",
+                    ),
+                ]
+                .as_slice(),
+            ),
+            CheckResult::Uncertain(UncertaintyReason::MessageSizeExceeded),
+        )
+        .await;
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn tls_required() {
+        check(
+            "hello@[127.0.0.1]",
+            RequestResponseList::from(
+                [
+                    ("EHLO example.com.", "250-mock\r\n250 STARTTLS"),
+                    (
+                        "MAIL FROM:<me@thomaszahner.ch>",
+                        "530 5.7.0 Must issue a STARTTLS command first",
+                    ),
+                ]
+                .as_slice(),
+            ),
+            CheckResult::Uncertain(UncertaintyReason::TlsRequired),
+        )
+        .await;
+    }
+
+    #[derive(Debug)]
+    struct AlwaysExists;
+
+    impl VerificationBackend for AlwaysExists {
+        fn verify<'a>(&'a self, _address: &'a str) -> BoxFuture<'a, CheckResult> {
+            Box::pin(async { CheckResult::Success })
+        }
+    }
+
+    /// Fake rejecting addresses of a single domain, as an application would inject in its tests
+    #[derive(Debug)]
+    struct RejectsDomain(&'static str);
+
+    impl VerificationBackend for RejectsDomain {
+        fn verify<'a>(&'a self, address: &'a str) -> BoxFuture<'a, CheckResult> {
+            Box::pin(async move {
+                if address.ends_with(self.0) {
+                    CheckResult::Failure(FailureReason::NoSuchAddress)
+                } else {
+                    CheckResult::Success
+                }
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn injected_backend() {
+        /// Application logic depending on the trait rather than on `Client`
+        async fn accept_signup(checker: &dyn VerificationBackend, address: &str) -> bool {
+            !checker.verify(address).await.is_undeliverable()
+        }
+
+        let fake: Arc<dyn VerificationBackend> = Arc::new(RejectsDomain("@example.org"));
+        assert!(accept_signup(fake.as_ref(), "hello@example.com").await);
+        assert!(!accept_signup(fake.as_ref(), "hello@example.org").await);
+
+        let client: Arc<dyn VerificationBackend> = Arc::new(Client::default());
+        assert!(!accept_signup(client.as_ref(), "invalid").await);
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn fallback_backend() {
+        let config = Config {
+            fallback: Some(Arc::new(AlwaysExists)),
+            ..Default::default()
+        };
+
+        check_with_config(
+            "hello@[127.0.0.1]",
+            default_template!(("RCPT TO:<hello@[127.0.0.1]>", "554 Blocklisted by Spamhaus")),
+            CheckResult::Success,
+            config.clone(),
+        )
+        .await;
+
+        // Definitive results don't fall back
+        check_with_config(
+            "hello@[127.0.0.1]",
+            default_template!(("RCPT TO:<hello@[127.0.0.1]>", "550 No such user")),
+            CheckResult::Failure(FailureReason::NoSuchAddress),
+            config,
+        )
+        .await;
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn classification_rules() {
+        let disabled = "550 5.7.1 Mailbox disabled by policy";
+
+        check(
+            "hello@[127.0.0.1]",
+            default_template!(("RCPT TO:<hello@[127.0.0.1]>", disabled)),
+            CheckResult::Uncertain(UncertaintyReason::PolicyRejection),
+        )
+        .await;
+
+        let config = Config {
+            classification_rules: vec![
+                ClassificationRule::new(
+                    "5.7.1",
+                    CheckResult::Failure(FailureReason::NoSuchAddress),
+                )
+                .with_message("mailbox disabled"),
+            ],
+            ..Default::default()
+        };
+
+        check_with_config(
+            "hello@[127.0.0.1]",
+            default_template!(("RCPT TO:<hello@[127.0.0.1]>", disabled)),
+            CheckResult::Failure(FailureReason::NoSuchAddress),
+            config.clone(),
+        )
+        .await;
+
+        // Responses not matching any rule are classified as usual
+        check_with_config(
+            "hello@[127.0.0.1]",
+            default_template!((
+                "RCPT TO:<hello@[127.0.0.1]>",
+                "550 5.7.1 Access denied by policy"
+            )),
+            CheckResult::Uncertain(UncertaintyReason::PolicyRejection),
+            config,
+        )
+        .await;
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn recipient_timeout() {
+        let server = task::spawn(async move {
+            listen(default_template!(("RCPT TO:<hello@[127.0.0.1]>", ""))).await;
+        });
+
+        sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+        let result = Client::new(Config {
+            port: PORT,
+            timeout: Some(Duration::from_millis(500)),
+            ..Default::default()
+        })
+        .unwrap()
+        .check_detailed("hello@[127.0.0.1]")
+        .await;
+        server.await.unwrap();
+
+        assert_eq!(
+            result.result,
+            CheckResult::Uncertain(UncertaintyReason::RecipientTimeout)
+        );
+        assert_eq!(result.phase, Some(Phase::Rcpt));
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn hello_timeout() {
+        let config = Config {
+            timeout: Some(Duration::from_millis(500)),
+            ..Default::default()
+        };
+
+        check_with_config(
+            "hello@[127.0.0.1]",
+            RequestResponseList::from([("EHLO example.com.", "")].as_slice()),
+            CheckResult::Uncertain(UncertaintyReason::Timeout),
+            config,
+        )
+        .await;
+    }
+
+    /// With paused time the runtime advances the clock as soon as it's idle,
+    /// so even long timeouts are tested without actually waiting
+    #[serial]
+    #[tokio::test(start_paused = true)]
+    async fn greeting_timeout_paused() {
+        // The operating system accepts the connection, but the greeting never comes
+        let _listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], PORT)))
+            .await
+            .unwrap();
+
+        let timeout = Duration::from_mins(5);
+        let start = Instant::now();
+        let wall_clock = std::time::Instant::now();
+        let result = Client::new(Config {
+            port: PORT,
+            timeout: Some(timeout),
+            ..Default::default()
+        })
+        .unwrap()
+        .check_detailed("hello@[127.0.0.1]")
+        .await;
+
+        assert_eq!(
+            result.result,
+            CheckResult::Uncertain(UncertaintyReason::Timeout)
+        );
+        assert_eq!(result.phase, Some(Phase::Greeting));
+        assert!(start.elapsed() >= timeout);
+        assert!(result.timings.greeting >= timeout - Duration::from_secs(1));
+        assert!(wall_clock.elapsed() < Duration::from_secs(5));
+    }
+
+    async fn check_catch_all(probe_response: &str, config: Config) -> DetailedResult {
+        let list = RequestResponseList::from(
+            [
+                ("EHLO example.com.", "250 OK"),
+                ("MAIL FROM:<me@thomaszahner.ch>", "250 OK"),
+                ("RCPT TO:<hello@[127.0.0.1]>", "250 OK"),
+                ("RCPT TO:<*@[127.0.0.1]>", probe_response),
+            ]
+            .as_slice(),
+        );
+        let server = task::spawn(async move {
+            listen(list).await;
+        });
+
+        sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+        let result = Client::new(Config {
+            port: PORT,
+            strategies: StrategyChain::new(vec![Strategy::Rcpt, Strategy::CatchAllProbe]),
+            ..config
+        })
+        .unwrap()
+        .check_detailed("hello@[127.0.0.1]")
+        .await;
+        server.await.unwrap();
+        result
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn catch_all() {
+        let result = check_catch_all("250 OK", Config::default()).await;
+        assert_eq!(result.result, CheckResult::Success);
+        assert_eq!(result.catch_all, Some(true));
+        assert!(!result.authoritative);
+
+        let config = Config {
+            demote_catch_all: true,
+            ..Default::default()
+        };
+        let result = check_catch_all("250 OK", config).await;
+        assert_eq!(
+            result.result,
+            CheckResult::Uncertain(UncertaintyReason::CatchAll)
+        );
+        assert_eq!(result.catch_all, Some(true));
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn no_catch_all() {
+        let config = Config {
+            demote_catch_all: true,
+            ..Default::default()
+        };
+        let result = check_catch_all("550 5.1.1 No such user", config).await;
+        assert_eq!(result.result, CheckResult::Success);
+        assert_eq!(result.catch_all, Some(false));
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn catch_all_probe_classification_rules() {
+        // A policy rejection alone is inconclusive
+        let result = check_catch_all("550 5.7.1 Mailbox disabled", Config::default()).await;
+        assert_eq!(result.catch_all, None);
+
+        let config = Config {
+            classification_rules: vec![ClassificationRule {
+                code: Some("5.7.1".into()),
+                message: Some("mailbox disabled".into()),
+                anchored: false,
+                result: CheckResult::Failure(FailureReason::NoSuchAddress),
+            }],
+            ..Default::default()
+        };
+        let result = check_catch_all("550 5.7.1 Mailbox disabled", config).await;
+        assert_eq!(result.result, CheckResult::Success);
+        assert_eq!(result.catch_all, Some(false));
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn catch_all_not_probed() {
+        let server = task::spawn(async move {
+            listen(default_template!(("RCPT TO:<hello@[127.0.0.1]>", "250 OK"))).await;
+        });
+
+        sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+        let result = Client::new(Config {
+            port: PORT,
+            ..Default::default()
+        })
+        .unwrap()
+        .check_detailed("hello@[127.0.0.1]")
+        .await;
+        server.await.unwrap();
+
+        assert_eq!(result.result, CheckResult::Success);
+        assert_eq!(result.catch_all, None);
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn port_override() {
+        let server = task::spawn(async move {
+            listen(default_template!(("RCPT TO:<hello@[127.0.0.1]>", "250 OK"))).await;
+        });
+
+        sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+        // Nothing listens on the default port
+        let result = Client::new(Config {
+            port: 1,
+            ports: HashMap::from([("127.0.0.1".into(), PORT)]),
+            ..Default::default()
+        })
+        .unwrap()
+        .check("hello@[127.0.0.1]")
+        .await;
+        assert_eq!(result, CheckResult::Success);
+
+        server.await.unwrap();
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn fallback_ports() {
+        let server = task::spawn(async move {
+            listen(default_template!(("RCPT TO:<hello@[127.0.0.1]>", "250 OK"))).await;
+        });
+
+        sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+        // Nothing listens on the first two ports
+        let result = Client::new(Config {
+            port: 1,
+            fallback_ports: vec![2, PORT],
+            ..Default::default()
+        })
+        .unwrap()
+        .check_detailed("hello@[127.0.0.1]")
+        .await;
+        server.await.unwrap();
+
+        assert_eq!(result.result, CheckResult::Success);
+        assert_eq!(result.port, Some(PORT));
+        // Domain literals aren't looked up
+        assert_eq!(result.mx_count, 0);
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn double_check() {
+        let greylisted = "450 4.2.0 Greylisted, please retry in 5 minutes";
+        let second_responses = [
+            ("250 OK", CheckResult::Success),
+            (
+                "550 5.1.1 User unknown",
+                CheckResult::Failure(FailureReason::NoSuchAddress),
+            ),
+        ];
+
+        for (second_response, expected) in second_responses {
+            let server = task::spawn(async move {
+                listen(default_template!((
+                    "RCPT TO:<hello@[127.0.0.1]>",
+                    greylisted
+                )))
+                .await;
+                listen(default_template!((
+                    "RCPT TO:<hello@[127.0.0.1]>",
+                    second_response
+                )))
+                .await;
+            });
+
+            sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+            let result = Client::new(Config {
+                port: PORT,
+                double_check: Some(Duration::from_millis(100)),
+                ..Default::default()
+            })
+            .unwrap()
+            .check("hello@[127.0.0.1]")
+            .await;
+            server.await.unwrap();
+
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn peer_addr() {
+        let server = task::spawn(async move {
+            listen(default_template!(("RCPT TO:<hello@example.com>", "250 OK"))).await;
+        });
+
+        sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+        let result = Client::new(Config {
+            port: PORT,
+            ..Default::default()
+        })
+        .unwrap()
+        .check_via("hello@example.com", "localhost")
+        .await;
+        server.await.unwrap();
+
+        assert_eq!(result.result, CheckResult::Success);
+        assert_eq!(
+            result.peer_addr,
+            Some(SocketAddr::from(([127, 0, 0, 1], PORT)))
+        );
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn fallback_ports_unreachable() {
+        let result = Client::new(Config {
+            port: 1,
+            fallback_ports: vec![2],
+            ..Default::default()
+        })
+        .unwrap()
+        .check_detailed("hello@[127.0.0.1]")
+        .await;
+
+        assert!(matches!(
+            result.result,
+            CheckResult::Failure(FailureReason::IoError(_))
+        ));
+        assert_eq!(result.phase, Some(Phase::Connect));
+        assert_eq!(result.port, None);
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn blocklist_evidence() {
+        let server = task::spawn(async move {
+            listen(default_template!((
+                "RCPT TO:<hello@[127.0.0.1]>",
+                "554 5.7.1 Service unavailable; Client host [192.0.2.1] blocked"
+            )))
+            .await;
+        });
+
+        sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+        let result = Client::new(Config {
+            port: PORT,
+            ..Default::default()
+        })
+        .unwrap()
+        .check_detailed("hello@[127.0.0.1]")
+        .await;
+        server.await.unwrap();
+
+        assert_eq!(
+            result.result,
+            CheckResult::Uncertain(UncertaintyReason::Blocklisted)
+        );
+        let evidence = result.blocklist_evidence.unwrap();
+        assert_eq!(evidence.word, "blocked");
+        assert_eq!(
+            evidence.response.message,
+            ["5.7.1 Service unavailable; Client host [192.0.2.1] blocked"]
+        );
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn trusted_provider() {
+        let server = task::spawn(async move {
+            // No catch-all probe
+            listen(default_template!(("RCPT TO:<hello@[127.0.0.1]>", "250 OK"))).await;
+        });
+
+        sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+        let result = Client::new(Config {
+            port: PORT,
+            strategies: StrategyChain::new(vec![Strategy::Rcpt, Strategy::CatchAllProbe]),
+            trusted_providers: vec!["[127.0.0.1]".into()],
+            ..Default::default()
+        })
+        .unwrap()
+        .check_detailed("hello@[127.0.0.1]")
+        .await;
+        server.await.unwrap();
+
+        assert_eq!(result.result, CheckResult::Success);
+        assert_eq!(result.catch_all, None);
+        assert!(result.authoritative);
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn warm_up() {
+        let server = task::spawn(async move {
+            // Accepts a single connection only
+            listen(default_template!(("RCPT TO:<hello@[127.0.0.1]>", "250 OK"))).await;
+        });
+
+        sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+        let client = Client::new(Config {
+            port: PORT,
+            park_connections: true,
+            timeout: Some(Duration::from_secs(1)),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(client.warm_up("[127.0.0.1]").await, CheckResult::Success);
+        assert_eq!(
+            client.check("hello@[127.0.0.1]").await,
+            CheckResult::Success
+        );
+
+        server.await.unwrap();
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn warm_up_invalid_domain() {
+        assert_eq!(
+            Client::default().warm_up("not a domain").await,
+            CheckResult::Failure(FailureReason::InvalidAddressFormat)
+        );
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn internationalized_address() {
+        check(
+            "jörg@[127.0.0.1]",
+            RequestResponseList::from(
+                [
+                    ("EHLO example.com.", "250-mock\r\n250 SMTPUTF8"),
+                    ("MAIL FROM:<me@thomaszahner.ch> SMTPUTF8", "250 OK"),
+                    ("RCPT TO:<jörg@[127.0.0.1]>", "250 OK"),
+                ]
+                .as_slice(),
+            ),
+            CheckResult::Success,
+        )
+        .await;
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn internationalized_address_unsupported() {
+        check(
+            "jörg@[127.0.0.1]",
+            RequestResponseList::from(
+                [("EHLO example.com.", "250-mock\r\n250 8BITMIME")].as_slice(),
+            ),
+            CheckResult::Uncertain(UncertaintyReason::EaiUnsupported),
+        )
+        .await;
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn probe_local_part() {
+        let list = RequestResponseList::from(
+            [
+                ("EHLO example.com.", "250 OK"),
+                ("MAIL FROM:<me@thomaszahner.ch>", "250 OK"),
+                ("RCPT TO:<hello@[127.0.0.1]>", "250 OK"),
+                ("RCPT TO:<jane.doe.*@[127.0.0.1]>", "550 5.1.1 No such user"),
+            ]
+            .as_slice(),
+        );
+        let server = task::spawn(async move {
+            listen(list).await;
+        });
+
+        sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+        let result = Client::new(Config {
+            port: PORT,
+            strategies: StrategyChain::new(vec![Strategy::Rcpt, Strategy::CatchAllProbe]),
+            probe_local_part: ProbeLocalPart::Prefix("jane.doe.".into()),
+            ..Default::default()
+        })
+        .unwrap()
+        .check_detailed("hello@[127.0.0.1]")
+        .await;
+        server.await.unwrap();
+
+        assert_eq!(result.catch_all, Some(false));
+
+        let result = check_catch_all(
+            "250 OK",
+            Config {
+                probe_local_part: ProbeLocalPart::Custom(Arc::new(|| "probe".into())),
+                ..Default::default()
+            },
+        )
+        .await;
+        assert_eq!(result.catch_all, Some(true));
+        assert!(
+            result
+                .transcript
+                .iter()
+                .any(|line| line.line == "RCPT TO:<probe@[127.0.0.1]>")
+        );
+
+        // Sent like the actual recipient, which allows quoted local parts
+        let result = check_catch_all(
+            "250 OK",
+            Config {
+                probe_local_part: ProbeLocalPart::Custom(Arc::new(|| "\"probe me\"".into())),
+                ..Default::default()
+            },
+        )
+        .await;
+        assert_eq!(result.catch_all, Some(true));
+        assert!(
+            result
+                .transcript
+                .iter()
+                .any(|line| line.line == "RCPT TO:<\"probe me\"@[127.0.0.1]>")
+        );
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn endless_greeting() {
+        let limits = [
+            ResponseLimits::default(),
+            ResponseLimits {
+                max_greeting_lines: usize::MAX,
+                greeting_timeout: Some(Duration::from_millis(300)),
+                ..Default::default()
+            },
+        ];
+        for response_limits in limits {
+            let server = task::spawn(greet_endlessly(Duration::from_millis(10)));
+
+            sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+            let result = Client::new(Config {
+                port: PORT,
+                response_limits,
+                ..Default::default()
+            })
+            .unwrap()
+            .check_detailed("hello@[127.0.0.1]")
+            .await;
+            server.await.unwrap();
+
+            assert_eq!(
+                result.result,
+                CheckResult::Uncertain(UncertaintyReason::ProtocolError)
+            );
+            assert_eq!(result.phase, Some(Phase::Greeting));
+        }
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn banner_delay() {
+        async fn check_delayed(delay: u64, timeout: u64) -> DetailedResult {
+            let server = task::spawn(greet_after(
+                Duration::from_millis(delay),
+                default_template!(("RCPT TO:<hello@[127.0.0.1]>", "250 OK")),
+            ));
+
+            sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+            let result = Client::new(Config {
+                port: PORT,
+                timeout: Some(Duration::from_millis(timeout)),
+                banner_delay_threshold: Some(Duration::from_millis(200)),
+                ..Default::default()
+            })
+            .unwrap()
+            .check_detailed("hello@[127.0.0.1]")
+            .await;
+            server.await.unwrap();
+            result
+        }
+
+        let prompt = check_delayed(0, 2000).await;
+        assert_eq!(prompt.result, CheckResult::Success);
+        assert!(!prompt.banner_delayed);
+
+        // Tolerated, but flagged
+        let delayed = check_delayed(400, 2000).await;
+        assert_eq!(delayed.result, CheckResult::Success);
+        assert!(delayed.banner_delayed);
+
+        let timed_out = check_delayed(600, 300).await;
+        assert_eq!(
+            timed_out.result,
+            CheckResult::Uncertain(UncertaintyReason::Blocklisted)
+        );
+        assert_eq!(timed_out.phase, Some(Phase::Greeting));
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn closed_before_greeting() {
+        for reset in [false, true] {
+            let server = task::spawn(accept_and_close(reset));
+
+            sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+            let result = Client::new(Config {
+                port: PORT,
+                ..Default::default()
+            })
+            .unwrap()
+            .check_detailed("hello@[127.0.0.1]")
+            .await;
+            server.await.unwrap();
+
+            assert_eq!(
+                result.result,
+                CheckResult::Uncertain(UncertaintyReason::Blocklisted)
+            );
+            assert_eq!(result.phase, Some(Phase::Greeting));
+        }
+    }
+
+    fn with_strategies(strategies: Vec<Strategy>) -> Config {
+        Config {
+            strategies: StrategyChain::new(strategies),
+            ..Default::default()
+        }
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn vrfy_then_rcpt() {
+        let strategies = vec![Strategy::Vrfy, Strategy::Rcpt];
+
+        check_with_config(
+            "hello@[127.0.0.1]",
+            RequestResponseList::from(
+                [
+                    ("EHLO example.com.", "250 OK"),
+                    ("MAIL FROM:<me@thomaszahner.ch>", "250 OK"),
+                    ("VRFY hello@[127.0.0.1]", "250 <hello@[127.0.0.1]>"),
+                ]
+                .as_slice(),
+            ),
+            CheckResult::Success,
+            with_strategies(strategies.clone()),
+        )
+        .await;
+
+        check_with_config(
+            "hello@[127.0.0.1]",
+            RequestResponseList::from(
+                [
+                    ("EHLO example.com.", "250 OK"),
+                    ("MAIL FROM:<me@thomaszahner.ch>", "250 OK"),
+                    ("VRFY hello@[127.0.0.1]", "550 5.1.1 No such user"),
+                ]
+                .as_slice(),
+            ),
+            CheckResult::Failure(FailureReason::NoSuchAddress),
+            with_strategies(strategies.clone()),
+        )
+        .await;
+
+        // Unsupported VRFY falls back on RCPT TO
+        check_with_config(
+            "hello@[127.0.0.1]",
+            RequestResponseList::from(
+                [
+                    ("EHLO example.com.", "250 OK"),
+                    ("MAIL FROM:<me@thomaszahner.ch>", "250 OK"),
+                    ("VRFY hello@[127.0.0.1]", "252 Cannot VRFY user"),
+                    ("RCPT TO:<hello@[127.0.0.1]>", "550 No such user"),
+                ]
+                .as_slice(),
+            ),
+            CheckResult::Failure(FailureReason::NoSuchAddress),
+            with_strategies(strategies),
+        )
+        .await;
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn vrfy_only() {
+        check_with_config(
+            "hello@[127.0.0.1]",
+            RequestResponseList::from(
+                [
+                    ("EHLO example.com.", "250 OK"),
+                    ("MAIL FROM:<me@thomaszahner.ch>", "250 OK"),
+                    (
+                        "VRFY hello@[127.0.0.1]",
+                        "502 5.5.1 VRFY command is disabled",
+                    ),
+                ]
+                .as_slice(),
+            ),
+            CheckResult::Uncertain(UncertaintyReason::Inconclusive),
+            with_strategies(vec![Strategy::Vrfy, Strategy::CatchAllProbe]),
+        )
+        .await;
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn exhaustive_chain() {
+        let server = task::spawn(async move {
+            listen(RequestResponseList::from(
+                [
+                    ("EHLO example.com.", "250 OK"),
+                    ("MAIL FROM:<me@thomaszahner.ch>", "250 OK"),
+                    ("VRFY hello@[127.0.0.1]", "550 5.1.1 No such user"),
+                    ("RCPT TO:<hello@[127.0.0.1]>", "250 OK"),
+                    ("RCPT TO:<*@[127.0.0.1]>", "550 5.1.1 No such user"),
+                ]
+                .as_slice(),
+            ))
+            .await;
+        });
+
+        sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+        let result = Client::new(Config {
+            port: PORT,
+            chain_mode: ChainMode::Exhaustive,
+            ..with_strategies(vec![
+                Strategy::Vrfy,
+                Strategy::Rcpt,
+                Strategy::CatchAllProbe,
+            ])
+        })
+        .unwrap()
+        .check_detailed("hello@[127.0.0.1]")
+        .await;
+        server.await.unwrap();
+
+        // The rejection by VRFY didn't end the check
+        assert_eq!(
+            result.strategy_results,
+            [
+                (
+                    Strategy::Vrfy,
+                    CheckResult::Failure(FailureReason::NoSuchAddress)
+                ),
+                (Strategy::Rcpt, CheckResult::Success),
+            ]
+        );
+        assert_eq!(result.catch_all, Some(false));
+        assert_eq!(
+            result.result,
+            CheckResult::Uncertain(UncertaintyReason::Conflicting)
+        );
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn timings() {
+        let server = task::spawn(async move {
+            listen(default_template!(("RCPT TO:<hello@[127.0.0.1]>", "250 OK"))).await;
+        });
+
+        sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+        let result = Client::new(Config {
+            port: PORT,
+            ..Default::default()
+        })
+        .unwrap()
+        .check_detailed("hello@[127.0.0.1]")
+        .await;
+        server.await.unwrap();
+
+        let timings = result.timings;
+        let phases = [
+            Phase::Dns,
+            Phase::Connect,
+            Phase::Greeting,
+            Phase::Hello,
+            Phase::MailFrom,
+            Phase::Vrfy,
+            Phase::Rcpt,
+            Phase::CatchAllProbe,
+        ];
+        let sum: Duration = phases.into_iter().map(|phase| timings.phase(phase)).sum();
+
+        assert!(timings.connect > Duration::ZERO);
+        assert!(timings.rcpt > Duration::ZERO);
+        assert_eq!(timings.vrfy, Duration::ZERO);
+        assert!(sum <= timings.total);
+        assert!(timings.total - sum < Duration::from_millis(50));
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn check_many_deadline() {
+        let server = task::spawn(async move {
+            // Stops responding
+            listen(RequestResponseList::from(
+                [("EHLO example.com.", "")].as_slice(),
+            ))
+            .await;
+        });
+
+        sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+        let client = Client::new(Config {
+            port: PORT,
+            ..Default::default()
+        })
+        .unwrap();
+        let addresses = vec!["hello@[127.0.0.1]".to_string(), "fast@".to_string()];
+        let results = client
+            .check_many(addresses, Duration::from_millis(500))
+            .await;
+
+        assert_eq!(
+            results,
+            [
+                (
+                    "hello@[127.0.0.1]".to_string(),
+                    CheckResult::Uncertain(UncertaintyReason::Timeout)
+                ),
+                (
+                    "fast@".to_string(),
+                    CheckResult::Failure(FailureReason::InvalidAddressFormat)
+                ),
+            ]
+        );
+
+        server.abort();
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn outbound_port_blocked() {
+        // Nothing listens on port 1, so connecting is refused
+        let client = Client::new(Config {
+            port: 1,
+            ..Default::default()
+        })
+        .unwrap();
+
+        for address in ["a@[127.0.0.1]", "b@[127.0.0.2]"] {
+            assert!(matches!(
+                client.check(address).await,
+                CheckResult::Failure(FailureReason::IoError(_))
+            ));
+        }
+        assert_eq!(
+            client.check("c@[127.0.0.3]").await,
+            CheckResult::Uncertain(UncertaintyReason::OutboundPort25Blocked)
+        );
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn check_via() {
+        let server = task::spawn(async move {
+            listen(default_template!(("RCPT TO:<hello@example.com>", "250 OK"))).await;
+        });
+
+        sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+        let client = Client::new(Config {
+            port: PORT,
+            ..Default::default()
+        })
+        .unwrap();
+        let result = client.check_via("hello@example.com", "localhost").await;
+        server.await.unwrap();
+
+        assert_eq!(result.result, CheckResult::Success);
+
+        let result = client.check_via("hello@example.com", "not a host").await;
+        assert_eq!(
+            result.result,
+            CheckResult::Failure(FailureReason::InvalidAddressFormat)
+        );
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn check_all_mx() {
+        let server = task::spawn(async move {
+            listen(default_template!(("RCPT TO:<hello@[127.0.0.1]>", "250 OK"))).await;
+        });
+
+        sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+        let client = Client::new(Config {
+            port: PORT,
+            ..Default::default()
+        })
+        .unwrap();
+        let results = client.check_all_mx("hello@[127.0.0.1]").await;
+        server.await.unwrap();
+
+        assert_eq!(
+            results,
+            Ok(vec![("127.0.0.1".to_string(), CheckResult::Success)])
+        );
+        assert_eq!(
+            client.check_all_mx("hello").await,
+            Err(CheckResult::Failure(FailureReason::InvalidAddressFormat))
+        );
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn trailing_dot() {
+        let client = Client::new(Config {
+            port: PORT,
+            ..Default::default()
+        })
+        .unwrap();
+
+        for address in ["hello@example.com", "hello@example.com."] {
+            let server = task::spawn(async move {
+                listen(default_template!(("RCPT TO:<hello@example.com>", "250 OK"))).await;
+            });
+
+            sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+            let result = client.check_via(address, "localhost").await;
+            server.await.unwrap();
+            assert_eq!(result.result, CheckResult::Success);
+        }
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn mx_overrides() {
+        check_with_config(
+            "hello@Example.com",
+            default_template!(("RCPT TO:<hello@example.com>", "250 OK")),
+            CheckResult::Success,
+            Config {
+                mx_overrides: HashMap::from([("example.com.".into(), "127.0.0.1".into())]),
+                ..Default::default()
+            },
+        )
+        .await;
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn normalized_idn_domain() {
+        let server = task::spawn(async move {
+            listen(RequestResponseList::from(
+                [
+                    ("EHLO example.com.", "250 OK"),
+                    ("MAIL FROM:<me@thomaszahner.ch>", "250 OK"),
+                    ("RCPT TO:<John.Doe@xn--bcher-kva.example>", "250 OK"),
+                    (
+                        "RCPT TO:<probe.*@xn--bcher-kva.example>",
+                        "550 5.1.1 User unknown",
+                    ),
+                ]
+                .as_slice(),
+            ))
+            .await;
+        });
+
+        sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+        // Every form of the domain in the configuration refers to the same domain
+        let config = Config {
+            port: PORT,
+            mx_overrides: HashMap::from([("bücher.EXAMPLE".into(), "127.0.0.1".into())]),
+            strategies: StrategyChain::new(vec![Strategy::Rcpt, Strategy::CatchAllProbe]),
+            probe_local_part: ProbeLocalPart::Prefix("probe.".into()),
+            ..Default::default()
+        };
+        let result = Client::new(config.clone())
+            .unwrap()
+            .check_detailed("John.Doe@BÜcher.Example")
+            .await;
+        server.await.unwrap();
+
+        assert_eq!(result.result, CheckResult::Success);
+        assert_eq!(result.catch_all, Some(false));
+
+        let server = task::spawn(async move {
+            listen(default_template!((
+                "RCPT TO:<John.Doe@xn--bcher-kva.example>",
+                "250 OK"
+            )))
+            .await;
+        });
+
+        sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+        let result = Client::new(Config {
+            catch_all_providers: vec!["XN--BCHER-KVA.example.".into()],
+            ..config
+        })
+        .unwrap()
+        .check_detailed("John.Doe@BÜcher.Example")
+        .await;
+        server.await.unwrap();
+
+        assert_eq!(
+            result.result,
+            CheckResult::Uncertain(UncertaintyReason::CatchAll)
+        );
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn catch_all_provider() {
+        let server = task::spawn(async move {
+            listen(default_template!((
+                "RCPT TO:<a309f2f034590l290@yahoo.com>",
+                "250 OK"
+            )))
+            .await;
+        });
+
+        sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+        let client = Client::new(Config {
+            port: PORT,
+            strategies: StrategyChain::new(vec![Strategy::Rcpt, Strategy::CatchAllProbe]),
+            ..Default::default()
+        })
+        .unwrap();
+        let result = client
+            .check_via("a309f2f034590l290@yahoo.com", "localhost")
+            .await;
+        server.await.unwrap();
+
+        assert_eq!(
+            result.result,
+            CheckResult::Uncertain(UncertaintyReason::CatchAll)
+        );
+        assert_eq!(result.catch_all, Some(true));
+        // Not probed, as the result is known already
+        assert_eq!(result.phase, Some(Phase::Rcpt));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn strip_subaddress() {
+        let server = task::spawn(async move {
+            listen(default_template!((
+                "RCPT TO:<john.doe@gmail.com>",
+                "250 OK"
+            )))
+            .await;
+        });
+
+        sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+        let client = Client::new(Config {
+            port: PORT,
+            strip_subaddress: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let result = client
+            .check_via("john.doe+newsletter@gmail.com", "localhost")
+            .await;
+        server.await.unwrap();
+        assert_eq!(result.result, CheckResult::Success);
+        assert_eq!(result.domain.unwrap().ascii(), "gmail.com");
+
+        let server = task::spawn(async move {
+            listen(default_template!((
+                "RCPT TO:<john.doe+newsletter@gmail.com>",
+                "550 No such user"
+            )))
+            .await;
+        });
+
+        sleep(Duration::from_millis(100)).await; // Prevent IO error: connection refused
+
+        let client = Client::new(Config {
+            port: PORT,
+            ..Default::default()
+        })
+        .unwrap();
+        let result = client
+            .check_via("john.doe+newsletter@gmail.com", "localhost")
+            .await;
+        server.await.unwrap();
+        assert_eq!(
+            result.result,
+            CheckResult::Failure(FailureReason::NoSuchAddress)
+        );
+    }
 }